@@ -0,0 +1,20 @@
+use std::io;
+
+use futures_util::{AsyncReadExt, AsyncWriteExt};
+use slings::fs::File;
+
+fn main() -> io::Result<()> {
+    slings::block_on(async {
+        let path = std::env::temp_dir().join("slings_file_example");
+        let mut file = File::create(&path).await?;
+        file.write_all(b"hello world").await?;
+        file.sync_all().await?;
+        file.close().await?;
+
+        let mut file = File::open(&path).await?;
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).await?;
+        println!("read {:?} from {:?}", buf, path);
+        Ok(())
+    })
+}
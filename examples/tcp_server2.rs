@@ -9,7 +9,7 @@ use slings::time::delay_for;
 fn main() -> io::Result<()> {
     slings::block_on(async {
         let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
-        let listener = TcpListener::bind(addr)?;
+        let listener = TcpListener::bind(addr).await?;
         println!("server start listen on {:?}", listener.local_addr());
         loop {
             let (mut stream, addr) = listener.accept2().await?;
@@ -7,7 +7,7 @@ use slings::time::delay_for;
 
 fn main() -> io::Result<()> {
     slings::block_on(async {
-        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
         println!("local addr: {}", socket.local_addr()?);
         let addr: SocketAddr = "127.0.0.1:8081".parse().unwrap();
         socket.connect(addr).await?;
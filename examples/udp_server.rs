@@ -6,7 +6,7 @@ use slings::time::delay_for;
 
 fn main() -> io::Result<()> {
     slings::block_on(async {
-        let socket = UdpSocket::bind("127.0.0.1:8081").unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:8081").await.unwrap();
         println!("udp bind on 127.0.0.1:8081");
         let mut buf = vec![0; 10];
         loop {
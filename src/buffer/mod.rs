@@ -2,7 +2,8 @@ use std::cell::Cell;
 use std::fmt;
 use std::io;
 use std::mem;
-use std::ops::{Deref, DerefMut};
+use std::ops::{Deref, DerefMut, Range};
+use std::os::unix::io::RawFd;
 use std::ptr;
 use std::rc::Rc;
 use std::sync::atomic;
@@ -10,9 +11,62 @@ use std::sync::atomic::AtomicU16;
 
 use io_uring::types::BufRingEntry;
 
+mod shared;
+
+pub use shared::SharedBuf;
+
 type Bgid = u16; // Buffer group id
 type Bid = u16; // Buffer id
 
+// The size of a default huge page on x86_64 and aarch64, explicit huge page mappings are rounded
+// up to a multiple of it.
+const HUGE_PAGE_SIZE: usize = 2 << 20;
+
+const IORING_REGISTER_PBUF_RING: libc::c_uint = 22;
+const IORING_UNREGISTER_PBUF_RING: libc::c_uint = 23;
+
+// Flags of `io_uring_buf_reg`. With IOU_PBUF_RING_MMAP the kernel allocates the ring and the
+// application maps it at IORING_OFF_PBUF_RING | bgid << IORING_OFF_PBUF_SHIFT of the io_uring fd
+// (6.4+). With IOU_PBUF_RING_INC a buffer may be consumed in several pieces (6.12+).
+const IOU_PBUF_RING_MMAP: u16 = 1;
+const IOU_PBUF_RING_INC: u16 = 2;
+const IORING_OFF_PBUF_RING: u64 = 0x8000_0000;
+const IORING_OFF_PBUF_SHIFT: u32 = 16;
+
+// Set on a cqe when the kernel keeps using the rest of an incrementally consumed buffer.
+const IORING_CQE_F_BUF_MORE: u32 = 1 << 4;
+
+// Returns whether the buffer of the completion represented by flags is still partly owned by the
+// kernel, which is only the case for incrementally consumed buffers.
+pub(crate) fn buf_more(flags: u32) -> bool {
+    flags & IORING_CQE_F_BUF_MORE != 0
+}
+
+// The argument of IORING_REGISTER_PBUF_RING, `struct io_uring_buf_reg`.
+#[repr(C)]
+struct BufReg {
+    ring_addr: u64,
+    ring_entries: u32,
+    bgid: u16,
+    flags: u16,
+    resv: [u64; 3],
+}
+
+/// How the memory backing the buffers of a buffer ring is allocated, see
+/// [`runtime::Builder::huge_pages`](crate::runtime::Builder::huge_pages).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum HugePages {
+    /// Regular pages.
+    #[default]
+    Never,
+    /// Regular pages advised with `MADV_HUGEPAGE`, the kernel may back them with transparent
+    /// huge pages.
+    Transparent,
+    /// Huge pages taken from the hugetlbfs pool with `MAP_HUGETLB`, this fails if the pool does
+    /// not have enough free pages.
+    Explicit,
+}
+
 // The Builder API for a BufRing.
 #[derive(Copy, Clone)]
 pub(crate) struct Builder {
@@ -20,6 +74,9 @@ pub(crate) struct Builder {
     ring_entries: u16,
     buf_cnt: u16,
     buf_len: usize,
+    huge_pages: HugePages,
+    kernel_mapped: bool,
+    incremental: bool,
 }
 
 impl Builder {
@@ -36,6 +93,9 @@ impl Builder {
             ring_entries: 128,
             buf_cnt: 0, // 0 indicates buf_cnt is taken from ring_entries
             buf_len: 4096,
+            huge_pages: HugePages::Never,
+            kernel_mapped: false,
+            incremental: false,
         }
     }
 
@@ -60,8 +120,30 @@ impl Builder {
         self
     }
 
-    // Return a BufRing.
-    pub fn build(&self) -> io::Result<BufRing> {
+    // Whether the buffers are backed by huge pages. All the buffers live in a single mapping of
+    // buf_cnt * buf_len bytes, so huge pages reduce the TLB pressure when that is large.
+    pub fn huge_pages(mut self, huge_pages: HugePages) -> Builder {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    // Whether the kernel allocates the ring entries, which are then mapped into userspace.
+    // Kernels without support fall back to a ring allocated by us.
+    pub fn kernel_mapped(mut self, kernel_mapped: bool) -> Builder {
+        self.kernel_mapped = kernel_mapped;
+        self
+    }
+
+    // Whether buffers are consumed incrementally, letting a buffer serve several completions
+    // until it is full instead of one each. Kernels without support fall back to one completion
+    // per buffer.
+    pub fn incremental(mut self, incremental: bool) -> Builder {
+        self.incremental = incremental;
+        self
+    }
+
+    // Return a BufRing registered with the io_uring instance of `ring_fd`.
+    pub fn build(&self, ring_fd: RawFd) -> io::Result<BufRing> {
         let mut b: Builder = *self;
 
         // Two cases where both buf_cnt and ring_entries are set to the max of the two.
@@ -76,21 +158,147 @@ impl Builder {
         // larger than 2^15 anyway, so this is a good place to catch it. Here we return a unique
         // error that is more descriptive than the InvalidArg that would come from the interface.
         if b.ring_entries > (1 << 15) {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "ring_entries exceeded 32768",
-            ));
+            return Err(io::Error::other("ring_entries exceeded 32768"));
         }
 
         // Requirement of the interface is the ring entries is a power of two, making its and our
         // wrap calculation trivial.
         b.ring_entries = b.ring_entries.next_power_of_two();
 
-        let inner = InnerBufRing::new(b.bgid, b.ring_entries, b.buf_cnt, b.buf_len)?;
+        let inner = InnerBufRing::new(&b, ring_fd)?;
         Ok(BufRing {
             inner: Rc::new(inner),
         })
     }
+
+    // Register a ring of `ring_size` bytes, returning its memory and whether buffers are consumed
+    // incrementally. The modes asked for are probed from the most capable one down to a user
+    // allocated ring consuming whole buffers, which is what every kernel with buffer rings (5.19+)
+    // supports. Unsupported modes are rejected with EINVAL.
+    fn register(&self, ring_fd: RawFd, ring_size: usize) -> io::Result<(Mmap, bool)> {
+        let incremental = if self.incremental {
+            &[true, false][..]
+        } else {
+            &[false][..]
+        };
+
+        if self.kernel_mapped {
+            for &inc in incremental {
+                let flags = IOU_PBUF_RING_MMAP | if inc { IOU_PBUF_RING_INC } else { 0 };
+                match self.register_kernel_mapped(ring_fd, ring_size, flags) {
+                    Ok(ring_start) => return Ok((ring_start, inc)),
+                    Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {}
+                    Err(e) => return Err(register_error(e, self.bgid)),
+                }
+            }
+        }
+
+        let ring_start = Mmap::new(ring_size)?;
+        ring_start.dontfork()?;
+        let mut last_err = None;
+        for &inc in incremental {
+            let flags = if inc { IOU_PBUF_RING_INC } else { 0 };
+            // Safety: The ring, represented by the ring_start and the ring_entries remains valid
+            // until it is unregistered. The backing store is an AnonymousMmap which remains valid
+            // until it is dropped which in this case, is when the BufRing is dropped.
+            let res = register_buf_ring(
+                ring_fd,
+                ring_start.as_ptr() as u64,
+                self.ring_entries,
+                self.bgid,
+                flags,
+            );
+            match res {
+                Ok(()) => return Ok((ring_start, inc)),
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) => last_err = Some(e),
+                Err(e) => return Err(register_error(e, self.bgid)),
+            }
+        }
+        Err(register_error(last_err.unwrap(), self.bgid))
+    }
+
+    fn register_kernel_mapped(
+        &self,
+        ring_fd: RawFd,
+        ring_size: usize,
+        flags: u16,
+    ) -> io::Result<Mmap> {
+        register_buf_ring(ring_fd, 0, self.ring_entries, self.bgid, flags)?;
+        let offset = IORING_OFF_PBUF_RING | (self.bgid as u64) << IORING_OFF_PBUF_SHIFT;
+        Mmap::from_fd(ring_fd, ring_size, offset as libc::off_t)
+            .and_then(|ring_start| ring_start.dontfork().map(|()| ring_start))
+            .inspect_err(|_| {
+                let _ = unregister_buf_ring(ring_fd, self.bgid);
+            })
+    }
+}
+
+fn register_buf_ring(
+    ring_fd: RawFd,
+    ring_addr: u64,
+    ring_entries: u16,
+    bgid: Bgid,
+    flags: u16,
+) -> io::Result<()> {
+    let arg = BufReg {
+        ring_addr,
+        ring_entries: ring_entries as u32,
+        bgid,
+        flags,
+        resv: [0; 3],
+    };
+    syscall!(syscall(
+        libc::SYS_io_uring_register,
+        ring_fd,
+        IORING_REGISTER_PBUF_RING,
+        &arg as *const BufReg,
+        1 as libc::c_uint,
+    ))?;
+    Ok(())
+}
+
+fn unregister_buf_ring(ring_fd: RawFd, bgid: Bgid) -> io::Result<()> {
+    let arg = BufReg {
+        ring_addr: 0,
+        ring_entries: 0,
+        bgid,
+        flags: 0,
+        resv: [0; 3],
+    };
+    syscall!(syscall(
+        libc::SYS_io_uring_register,
+        ring_fd,
+        IORING_UNREGISTER_PBUF_RING,
+        &arg as *const BufReg,
+        1 as libc::c_uint,
+    ))?;
+    Ok(())
+}
+
+fn register_error(e: io::Error, bgid: Bgid) -> io::Error {
+    match e.raw_os_error() {
+        Some(libc::EINVAL) => {
+            // using buf_ring requires kernel 5.19 or greater.
+            io::Error::other(format!(
+                "buf_ring.register returned {}, most likely indicating this kernel is not 5.19+",
+                e
+            ))
+        }
+        Some(libc::EEXIST) => {
+            // Registering a duplicate bgid is not allowed. There is an `unregister`
+            // operations that can remove the first, but care must be taken that there
+            // are no outstanding operations that will still return a buffer from that
+            // one.
+            io::Error::other(format!(
+                "buf_ring.register returned `{}`, indicating the attempted buffer group id {} was already registered",
+                e, bgid
+            ))
+        }
+        _ => io::Error::other(format!(
+            "buf_ring.register returned `{}` for group id {}",
+            e, bgid
+        )),
+    }
 }
 
 // The BufRing is reference counted because each buffer handed
@@ -102,55 +310,68 @@ pub(crate) struct BufRing {
 
 impl BufRing {
     // Returns the buffer the uring interface picked from the buf_ring for the completion result
-    // represented by the res and flags.
-    pub fn get_buf(&self, len: usize, bid: u16) -> Buf {
-        self.inner.get_buf(self.clone(), len, bid)
+    // represented by the res and flags. `more` tells whether the kernel keeps using the rest of
+    // the buffer.
+    pub fn get_buf(&self, len: usize, bid: u16, more: bool) -> Buf {
+        self.inner.get_buf(self.clone(), len, bid, more)
     }
 
-    // Returns the buffer group id.
-    pub fn bgid(&self) -> Bgid {
-        self.inner.bgid()
+    // Release the buffer of a failed completion, `more` as for get_buf.
+    pub fn drop_buf(&self, bid: Bid, more: bool) {
+        self.inner.release(bid, more);
     }
 
-    pub fn ring_entries(&self) -> u16 {
-        self.inner.ring_entries()
-    }
-
-    /// Get a pointer to the memory.
-    pub fn as_ptr(&self) -> *const libc::c_void {
-        self.inner.ring_start.as_ptr()
-    }
-
-    pub fn drop_buf(&self, bid: Bid) {
-        self.inner.drop_buf(bid);
+    // Unregister the buf_ring from the io_uring instance of `ring_fd` it was built for. Buffers
+    // still handed out stay valid, the memory is only released once the last of them is dropped.
+    pub fn unregister(&self, ring_fd: RawFd) -> io::Result<()> {
+        unregister_buf_ring(ring_fd, self.inner.bgid())
     }
 }
 
 // This tracks a buffer that has been filled in by the kernel, having gotten the memory
 // from a buffer ring, and returned to userland via a cqe entry.
-pub(crate) struct Buf {
+pub struct Buf {
     buf_ring: BufRing,
+    offset: usize,
     len: usize,
     bid: Bid,
 }
 
 impl Buf {
-    fn new(buf_ring: BufRing, bid: Bid, len: usize) -> Self {
-        assert!(len <= buf_ring.inner.buf_capacity());
-        Self { buf_ring, len, bid }
+    fn new(buf_ring: BufRing, bid: Bid, offset: usize, len: usize) -> Self {
+        assert!(offset + len <= buf_ring.inner.buf_capacity());
+        Self {
+            buf_ring,
+            offset,
+            len,
+            bid,
+        }
+    }
+
+    // Restrict the buffer to `range` of its current contents, for completions carrying a header
+    // before the data.
+    pub(crate) fn narrow(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.len);
+        self.offset += range.start;
+        self.len = range.end - range.start;
     }
 
     // Return a byte slice reference.
     fn as_slice_mut(&mut self) -> &mut [u8] {
-        let p = self.buf_ring.inner.stable_ptr(self.bid);
+        let p = unsafe { self.buf_ring.inner.stable_ptr(self.bid).add(self.offset) };
         unsafe { std::slice::from_raw_parts_mut(p as *mut _, self.len) }
     }
 
     // Return a byte slice reference.
     fn as_slice(&self) -> &[u8] {
-        let p = self.buf_ring.inner.stable_ptr(self.bid);
+        let p = unsafe { self.buf_ring.inner.stable_ptr(self.bid).add(self.offset) };
         unsafe { std::slice::from_raw_parts(p, self.len) }
     }
+
+    /// Convert into a `SharedBuf`, which can be cheaply cloned and sliced.
+    pub fn into_shared(self) -> SharedBuf {
+        SharedBuf::from(self)
+    }
 }
 
 impl fmt::Debug for Buf {
@@ -158,6 +379,7 @@ impl fmt::Debug for Buf {
         f.debug_struct("Buf")
             .field("bgid", &self.buf_ring.inner.bgid())
             .field("bid", &self.bid)
+            .field("offset", &self.offset)
             .field("len", &self.len)
             .field("cap", &self.buf_ring.inner.buf_capacity())
             .finish()
@@ -180,12 +402,24 @@ impl DerefMut for Buf {
 
 impl Drop for Buf {
     fn drop(&mut self) {
-        // Add the buffer back to the buf_ring, for the kernel to reuse.
+        // Add the buffer back to the buf_ring, for the kernel to reuse, once no other piece of it
+        // is in use.
         self.buf_ring.inner.drop_buf(self.bid);
     }
 }
 
-// All these fields are constant once the struct is instantiated except the one of type Cell<u16>.
+// Tracks the ownership of one buffer. With incremental consumption the kernel hands a buffer out
+// in pieces, so several `Buf`s may point into it at increasing offsets.
+struct BufState {
+    // Offset of the first byte the kernel has not handed out yet.
+    offset: Cell<usize>,
+    // Number of `Buf`s alive pointing into the buffer.
+    refs: Cell<u16>,
+    // Whether the kernel may still hand out pieces of the buffer.
+    in_kernel: Cell<bool>,
+}
+
+// All these fields are constant once the struct is instantiated except the ones of type Cell.
 struct InnerBufRing {
     bgid: Bgid,
 
@@ -198,7 +432,15 @@ struct InnerBufRing {
     // the buffers being made available to the uring interface for this buf group id.
     ring_start: Mmap,
 
-    buf_list: Vec<Vec<u8>>,
+    // `buf_start` holds the memory of all the buffers, one contiguous region where buffer `bid`
+    // starts at `bid * buf_len`.
+    buf_start: Mmap,
+
+    // Whether the kernel consumes buffers incrementally.
+    incremental: bool,
+
+    // The ownership of each buffer, indexed by `bid`.
+    buf_states: Vec<BufState>,
 
     // `local_tail` is the copy of the tail index that we update when a buffer is dropped and
     // therefore its buffer id is released and added back to the ring. It also serves for adding
@@ -213,12 +455,14 @@ struct InnerBufRing {
 }
 
 impl InnerBufRing {
-    fn new(
-        bgid: Bgid,
-        ring_entries: u16,
-        buf_cnt: u16,
-        buf_len: usize,
-    ) -> io::Result<InnerBufRing> {
+    fn new(b: &Builder, ring_fd: RawFd) -> io::Result<InnerBufRing> {
+        let Builder {
+            bgid,
+            ring_entries,
+            buf_cnt,
+            buf_len,
+            ..
+        } = *b;
         // Check that none of the important args are zero and the ring_entries is at least large
         // enough to hold all the buffers and that ring_entries is a power of 2.
         if (buf_cnt == 0)
@@ -232,17 +476,14 @@ impl InnerBufRing {
         let entry_size = mem::size_of::<BufRingEntry>();
         assert_eq!(entry_size, 16);
         let ring_size = entry_size * (ring_entries as usize);
-        let ring_start = Mmap::new(ring_size)?;
-        ring_start.dontfork()?;
 
-        // Probably some functional way to do this.
-        let buf_list: Vec<Vec<u8>> = {
-            let mut bp = Vec::with_capacity(buf_cnt as _);
-            for _ in 0..buf_cnt {
-                bp.push(vec![0; buf_len]);
-            }
-            bp
-        };
+        let buf_size = (buf_cnt as usize)
+            .checked_mul(buf_len)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let buf_start = Mmap::with_huge_pages(buf_size, b.huge_pages)?;
+        buf_start.dontfork()?;
+
+        let (ring_start, incremental) = b.register(ring_fd, ring_size)?;
 
         let shared_tail = unsafe { BufRingEntry::tail(ring_start.as_ptr() as *const BufRingEntry) }
             as *const AtomicU16;
@@ -253,7 +494,15 @@ impl InnerBufRing {
             buf_cnt,
             buf_len,
             ring_start,
-            buf_list,
+            buf_start,
+            incremental,
+            buf_states: (0..buf_cnt)
+                .map(|_| BufState {
+                    offset: Cell::new(0),
+                    refs: Cell::new(0),
+                    in_kernel: Cell::new(true),
+                })
+                .collect(),
             local_tail: Cell::new(0),
             shared_tail,
         };
@@ -299,19 +548,36 @@ impl InnerBufRing {
         }
     }
 
-    // Safety: dropping a duplicate bid is likely to cause undefined behavior
-    // as the kernel could use the same buffer for different data concurrently.
+    // Called when a `Buf` pointing into `bid` is dropped.
     fn drop_buf(&self, bid: Bid) {
+        let state = &self.buf_states[bid as usize];
+        state.refs.set(state.refs.get() - 1);
+        self.recycle(bid);
+    }
+
+    // Called when the kernel hands back `bid` without a `Buf`, as for failed completions.
+    fn release(&self, bid: Bid, more: bool) {
+        self.buf_states[bid as usize].in_kernel.set(more);
+        self.recycle(bid);
+    }
+
+    // Add `bid` back to the ring once neither the kernel nor any `Buf` is using it.
+    //
+    // Safety: pushing a duplicate bid is likely to cause undefined behavior
+    // as the kernel could use the same buffer for different data concurrently.
+    fn recycle(&self, bid: Bid) {
+        let state = &self.buf_states[bid as usize];
+        if state.refs.get() > 0 || state.in_kernel.get() {
+            return;
+        }
+        state.offset.set(0);
+        state.in_kernel.set(true);
         self.push(bid);
         self.sync();
     }
 
     fn stable_ptr(&self, bid: Bid) -> *const u8 {
-        self.buf_list[bid as usize].as_ptr()
-    }
-
-    fn ring_entries(&self) -> u16 {
-        self.ring_entries_mask + 1
+        unsafe { (self.buf_start.as_ptr() as *const u8).add(bid as usize * self.buf_len) }
     }
 
     fn mask(&self) -> u16 {
@@ -329,12 +595,20 @@ impl InnerBufRing {
 
     // Returns the buffer the uring interface picked from the buf_ring for the completion result
     // represented by the res and flags.
-    fn get_buf(&self, buf_ring: BufRing, len: usize, bid: u16) -> Buf {
+    fn get_buf(&self, buf_ring: BufRing, len: usize, bid: u16, more: bool) -> Buf {
         // This fn does the odd thing of having self as the BufRing and taking an argument that is
         // the same BufRing but wrapped in Rc<_> so the wrapped buf_ring can be passed to the
         // outgoing Buf.
         assert!(len <= self.buf_len);
-        Buf::new(buf_ring, bid, len)
+        let state = &self.buf_states[bid as usize];
+        // Without incremental consumption every completion gets a whole buffer, at offset 0.
+        let offset = state.offset.get();
+        if self.incremental {
+            state.offset.set(offset + len);
+        }
+        state.refs.set(state.refs.get() + 1);
+        state.in_kernel.set(more);
+        Buf::new(buf_ring, bid, offset, len)
     }
 }
 
@@ -347,14 +621,48 @@ struct Mmap {
 impl Mmap {
     /// Map `len` bytes into memory.
     fn new(len: usize) -> io::Result<Mmap> {
+        Mmap::anonymous(len, libc::MAP_SHARED | libc::MAP_POPULATE)
+    }
+
+    /// Map `len` private bytes into memory, backed by huge pages according to `huge_pages`.
+    fn with_huge_pages(len: usize, huge_pages: HugePages) -> io::Result<Mmap> {
+        match huge_pages {
+            HugePages::Never => Mmap::anonymous(len, libc::MAP_PRIVATE | libc::MAP_POPULATE),
+            HugePages::Transparent => {
+                // Populating before the advice would fault in regular pages, leave that to
+                // the first use instead.
+                let mmap = Mmap::anonymous(len, libc::MAP_PRIVATE)?;
+                mmap.madvise(libc::MADV_HUGEPAGE)?;
+                Ok(mmap)
+            }
+            HugePages::Explicit => {
+                let len = len.next_multiple_of(HUGE_PAGE_SIZE);
+                Mmap::anonymous(
+                    len,
+                    libc::MAP_PRIVATE | libc::MAP_POPULATE | libc::MAP_HUGETLB,
+                )
+            }
+        }
+    }
+
+    /// Map `len` bytes of the file `fd` at `offset` into memory, shared with the kernel.
+    fn from_fd(fd: RawFd, len: usize, offset: libc::off_t) -> io::Result<Mmap> {
+        Mmap::map(len, libc::MAP_SHARED | libc::MAP_POPULATE, fd, offset)
+    }
+
+    fn anonymous(len: usize, flags: libc::c_int) -> io::Result<Mmap> {
+        Mmap::map(len, libc::MAP_ANONYMOUS | flags, -1, 0)
+    }
+
+    fn map(len: usize, flags: libc::c_int, fd: RawFd, offset: libc::off_t) -> io::Result<Mmap> {
         unsafe {
             match libc::mmap(
                 ptr::null_mut(),
                 len,
                 libc::PROT_READ | libc::PROT_WRITE,
-                libc::MAP_ANONYMOUS | libc::MAP_SHARED | libc::MAP_POPULATE,
-                -1,
-                0,
+                flags,
+                fd,
+                offset,
             ) {
                 libc::MAP_FAILED => Err(io::Error::last_os_error()),
                 addr => {
@@ -368,7 +676,11 @@ impl Mmap {
 
     /// Do not make the stored memory accessible by child processes after a `fork`.
     fn dontfork(&self) -> io::Result<()> {
-        match unsafe { libc::madvise(self.addr.as_ptr(), self.len, libc::MADV_DONTFORK) } {
+        self.madvise(libc::MADV_DONTFORK)
+    }
+
+    fn madvise(&self, advice: libc::c_int) -> io::Result<()> {
+        match unsafe { libc::madvise(self.addr.as_ptr(), self.len, advice) } {
             0 => Ok(()),
             _ => Err(io::Error::last_os_error()),
         }
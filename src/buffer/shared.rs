@@ -0,0 +1,174 @@
+use std::fmt;
+use std::ops::{Bound, Deref, RangeBounds};
+use std::rc::Rc;
+
+use super::Buf;
+
+/// A cheaply cloneable and sliceable view into a [`Buf`].
+///
+/// All views created from the same `Buf` share it through a reference count, the buffer id
+/// is handed back to the buffer ring only once the last view has been dropped.
+#[derive(Clone)]
+pub struct SharedBuf {
+    buf: Rc<Buf>,
+    start: usize,
+    end: usize,
+}
+
+impl SharedBuf {
+    /// Returns the number of bytes in this view.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns true if this view contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns a view of the given range of `self`, sharing the underlying buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or `begin > end`.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> SharedBuf {
+        let len = self.len();
+        let begin = match range.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1).expect("out of range"),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&n) => n.checked_add(1).expect("out of range"),
+            Bound::Excluded(&n) => n,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            begin <= end,
+            "range start must not be greater than end: {:?} <= {:?}",
+            begin,
+            end,
+        );
+        assert!(
+            end <= len,
+            "range end out of bounds: {:?} <= {:?}",
+            end,
+            len
+        );
+
+        SharedBuf {
+            buf: self.buf.clone(),
+            start: self.start + begin,
+            end: self.start + end,
+        }
+    }
+
+    /// Splits the view into two at the given index.
+    ///
+    /// Afterwards `self` contains `[0, at)`, and the returned view contains `[at, len)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> SharedBuf {
+        assert!(
+            at <= self.len(),
+            "split_off out of bounds: {:?} <= {:?}",
+            at,
+            self.len(),
+        );
+        let other = SharedBuf {
+            buf: self.buf.clone(),
+            start: self.start + at,
+            end: self.end,
+        };
+        self.end = self.start + at;
+        other
+    }
+
+    /// Splits the view into two at the given index.
+    ///
+    /// Afterwards `self` contains `[at, len)`, and the returned view contains `[0, at)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_to(&mut self, at: usize) -> SharedBuf {
+        assert!(
+            at <= self.len(),
+            "split_to out of bounds: {:?} <= {:?}",
+            at,
+            self.len(),
+        );
+        let other = SharedBuf {
+            buf: self.buf.clone(),
+            start: self.start,
+            end: self.start + at,
+        };
+        self.start += at;
+        other
+    }
+
+    /// Shortens the view, keeping the first `len` bytes. Has no effect if `len` is greater
+    /// than the current length.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.end = self.start + len;
+        }
+    }
+
+    /// Returns the underlying `Buf` if this is the only view into it and it covers the whole
+    /// buffer, otherwise `self` is returned unchanged.
+    pub fn try_into_buf(self) -> Result<Buf, SharedBuf> {
+        if self.start != 0 || self.end != self.buf.len() {
+            return Err(self);
+        }
+        let SharedBuf { buf, start, end } = self;
+        Rc::try_unwrap(buf).map_err(|buf| SharedBuf { buf, start, end })
+    }
+}
+
+impl From<Buf> for SharedBuf {
+    fn from(buf: Buf) -> SharedBuf {
+        let end = buf.len();
+        SharedBuf {
+            buf: Rc::new(buf),
+            start: 0,
+            end,
+        }
+    }
+}
+
+impl Deref for SharedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf[self.start..self.end]
+    }
+}
+
+impl AsRef<[u8]> for SharedBuf {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for SharedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedBuf")
+            .field("buf", &self.buf)
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .finish()
+    }
+}
+
+// The buffer ring is thread local and reference counted with `Rc`, so the memory can't be
+// handed to `Bytes`, which requires its owner to be `Send`. The bytes are copied instead, and
+// the buffer is returned to the ring as soon as `self` is dropped.
+#[cfg(feature = "bytes")]
+impl From<SharedBuf> for bytes::Bytes {
+    fn from(buf: SharedBuf) -> bytes::Bytes {
+        bytes::Bytes::copy_from_slice(&buf)
+    }
+}
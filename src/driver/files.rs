@@ -0,0 +1,92 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+use io_uring::squeue::{self, Entry};
+use io_uring::{types, IoUring};
+
+// The number of slots in the registered file table. Slots below `FILE_ALLOC_START` are handed
+// out by `FileTable::register`, the others are left for the kernel to allocate direct
+// descriptors in, as done by accepting with `IORING_FILE_INDEX_ALLOC`.
+const FILE_TABLE_SIZE: u32 = 4096;
+const FILE_ALLOC_START: u32 = 2048;
+
+// Not yet exposed by the io-uring crate.
+const IORING_REGISTER_FILE_ALLOC_RANGE: libc::c_uint = 25;
+
+#[repr(C)]
+struct FileIndexRange {
+    off: u32,
+    len: u32,
+    resv: u64,
+}
+
+/// A file descriptor, or the index of a file in the registered file table.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Fd {
+    Raw(RawFd),
+    Fixed(u32),
+}
+
+impl Fd {
+    // Build an entry targeting this file. `build` is given the value of the sqe `fd` field,
+    // for registered files the `FIXED_FILE` flag is added afterwards, like `types::Fixed` does.
+    pub(crate) fn build(self, build: impl FnOnce(types::Fd) -> Entry) -> Entry {
+        match self {
+            Fd::Raw(fd) => build(types::Fd(fd)),
+            Fd::Fixed(index) => build(types::Fd(index as RawFd)).flags(squeue::Flags::FIXED_FILE),
+        }
+    }
+}
+
+pub(crate) struct FileTable {
+    free: Vec<u32>,
+}
+
+impl FileTable {
+    pub(crate) fn new(ring: &IoUring) -> io::Result<FileTable> {
+        ring.submitter().register_files_sparse(FILE_TABLE_SIZE)?;
+        if let Err(e) = register_file_alloc_range(
+            ring.as_raw_fd(),
+            FILE_ALLOC_START,
+            FILE_TABLE_SIZE - FILE_ALLOC_START,
+        ) {
+            let _ = ring.submitter().unregister_files();
+            return Err(e);
+        }
+        Ok(FileTable {
+            free: (0..FILE_ALLOC_START).rev().collect(),
+        })
+    }
+
+    // Install `fd` in a free slot, the slot holds its own reference to the file.
+    pub(crate) fn register(&mut self, ring: &IoUring, fd: RawFd) -> io::Result<u32> {
+        let index = self.free.pop().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::OutOfMemory, "registered file table is full")
+        })?;
+        if let Err(e) = ring.submitter().register_files_update(index, &[fd]) {
+            self.free.push(index);
+            return Err(e);
+        }
+        Ok(index)
+    }
+
+    // Clear the slot, closing the file once in-flight operations are done with it.
+    pub(crate) fn unregister(&mut self, ring: &IoUring, index: u32) {
+        let _ = ring.submitter().register_files_update(index, &[-1]);
+        if index < FILE_ALLOC_START {
+            self.free.push(index);
+        }
+    }
+}
+
+fn register_file_alloc_range(ring_fd: RawFd, off: u32, len: u32) -> io::Result<()> {
+    let arg = FileIndexRange { off, len, resv: 0 };
+    syscall!(syscall(
+        libc::SYS_io_uring_register,
+        ring_fd,
+        IORING_REGISTER_FILE_ALLOC_RANGE,
+        &arg as *const FileIndexRange,
+        0 as libc::c_uint,
+    ))?;
+    Ok(())
+}
@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use std::future::Future;
 use std::io;
 use std::mem;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::pin::Pin;
 use std::rc::Rc;
 use std::task::{Context, Poll, Waker};
@@ -12,13 +13,19 @@ use io_uring::{cqueue, opcode, IoUring};
 use scoped_tls::scoped_thread_local;
 use slab::Slab;
 
-use crate::buffer::{Buf, BufRing, Builder};
+use crate::buffer::{self, Buf, BufRing, Builder, HugePages};
 
+mod files;
 mod op;
 
+pub(crate) use files::Fd;
+use files::FileTable;
 pub(crate) use op::*;
 
 pub const BUF_BGID: u16 = 666;
+// The buffer group of datagram receives, which must get whole buffers: a datagram landing in the
+// rest of an incrementally consumed buffer would be truncated.
+pub const DGRAM_BUF_BGID: u16 = 667;
 const DEFAULT_RING_ENTRIES: u16 = 128;
 const DEFAULT_BUF_CNT: u16 = 128;
 const DEFAULT_BUF_LEN: usize = 4096;
@@ -39,77 +46,73 @@ impl Clone for Driver {
 
 struct Inner {
     buf_ring: BufRing,
+    dgram_buf_ring: BufRing,
+    // The error setting up the table if the kernel does not support sparse registered file
+    // tables, reported when registering a file.
+    files: io::Result<FileTable>,
     ring: IoUring,
     ops: Slab<Lifecycle>,
 }
 
 impl Inner {
-    fn new() -> io::Result<Inner> {
+    fn new(huge_pages: HugePages) -> io::Result<Inner> {
         let ring = IoUring::new(256)?;
         let buf_ring = Builder::new(BUF_BGID)
             .ring_entries(DEFAULT_RING_ENTRIES)
             .buf_cnt(DEFAULT_BUF_CNT)
             .buf_len(DEFAULT_BUF_LEN)
-            .build()?;
-        let mut inner = Inner {
+            .huge_pages(huge_pages)
+            .kernel_mapped(true)
+            .incremental(true)
+            .build(ring.as_raw_fd())?;
+        let dgram_buf_ring = Builder::new(DGRAM_BUF_BGID)
+            .ring_entries(DEFAULT_RING_ENTRIES)
+            .buf_cnt(DEFAULT_BUF_CNT)
+            .buf_len(DEFAULT_BUF_LEN)
+            .huge_pages(huge_pages)
+            .kernel_mapped(true)
+            .build(ring.as_raw_fd())?;
+        let files = FileTable::new(&ring);
+        Ok(Inner {
             ring,
             ops: Slab::with_capacity(256),
             buf_ring,
-        };
-        inner.register_buf_ring()?;
-        Ok(inner)
+            dgram_buf_ring,
+            files,
+        })
     }
 
-    fn register_buf_ring(&mut self) -> io::Result<()> {
-        // Safety: The ring, represented by the ring_start and the ring_entries remains valid until
-        // it is unregistered. The backing store is an AnonymousMmap which remains valid until it
-        // is dropped which in this case, is when Self is dropped.
-        let res = unsafe {
-            self.ring.submitter().register_buf_ring(
-                self.buf_ring.as_ptr() as _,
-                self.buf_ring.ring_entries(),
-                self.buf_ring.bgid(),
-            )
-        };
+    fn register_file(&mut self, fd: RawFd) -> io::Result<u32> {
+        match &mut self.files {
+            Ok(files) => files.register(&self.ring, fd),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("registered files are not supported: {e}"),
+            )),
+        }
+    }
 
-        if let Err(e) = res {
-            match e.raw_os_error() {
-                Some(libc::EINVAL) => {
-                    // using buf_ring requires kernel 5.19 or greater.
-                    return Err(io::Error::new(
-                            io::ErrorKind::Other, format!(
-                                "buf_ring.register returned {}, most likely indicating this kernel is not 5.19+", e),
-                            ));
-                }
-                Some(libc::EEXIST) => {
-                    // Registering a duplicate bgid is not allowed. There is an `unregister`
-                    // operations that can remove the first, but care must be taken that there
-                    // are no outstanding operations that will still return a buffer from that
-                    // one.
-                    return Err(io::Error::new(
-                            io::ErrorKind::Other,
-                            format!(
-                                "buf_ring.register returned `{}`, indicating the attempted buffer group id {} was already registered",
-                            e,
-                            self.buf_ring.bgid()),
-                        ));
-                }
-                _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        format!(
-                            "buf_ring.register returned `{}` for group id {}",
-                            e,
-                            self.buf_ring.bgid()
-                        ),
-                    ));
-                }
-            }
-        };
-        res
+    fn unregister_file(&mut self, index: u32) {
+        if let Ok(files) = &mut self.files {
+            files.unregister(&self.ring, index);
+        }
     }
 
     fn submit(&mut self, sqe: Entry) -> io::Result<()> {
+        self.push(sqe)?;
+        self.ring.submit()?;
+        Ok(())
+    }
+
+    // The number of entries that can be pushed before the submission queue is full.
+    fn free_entries(&mut self) -> usize {
+        let mut sq = self.ring.submission();
+        sq.sync();
+        sq.capacity() - sq.len()
+    }
+
+    // Push an entry to the submission queue, only flushing it if it is full.
+    fn push(&mut self, sqe: Entry) -> io::Result<()> {
         if self.ring.submission().is_full() {
             self.ring.submit()?;
         }
@@ -117,7 +120,6 @@ impl Inner {
         unsafe {
             self.ring.submission().push(&sqe).expect("push entry fail");
         }
-        self.ring.submit()?;
         Ok(())
     }
 
@@ -138,31 +140,72 @@ impl Inner {
             if cqe.user_data() == u64::MAX {
                 continue;
             }
-            let index = cqe.user_data() as _;
+            // The low half of the user data is the index of the op, the high half the buffer
+            // group it selects buffers from.
+            let index = cqe.user_data() as u32 as usize;
+            let buf_ring = match (cqe.user_data() >> 32) as u16 {
+                DGRAM_BUF_BGID => &self.dgram_buf_ring,
+                _ => &self.buf_ring,
+            };
             let op = &mut self.ops[index];
-            if op.complete(cqe, &self.buf_ring) {
+            if op.complete(cqe, buf_ring) {
                 self.ops.remove(index);
             }
         }
         Ok(())
     }
 
-    fn submit_op<T>(&mut self, driver: Driver, op: T, sqe: Entry) -> io::Result<Op<T>> {
+    // Push and flush the entry of `op`, handing `op` back if it cannot be pushed. Once pushed,
+    // the entry reaches the kernel with the next flush if this one fails, so the op lives on.
+    fn submit_op<T>(
+        &mut self,
+        driver: Driver,
+        op: T,
+        sqe: Entry,
+        bgid: u16,
+    ) -> Result<Op<T>, (io::Error, T)> {
+        let op = self.push_op(driver, op, sqe, bgid)?;
+        // Errors of the ring itself surface again when the driver waits for completions.
+        let _ = self.ring.submit();
+        Ok(op)
+    }
+
+    fn push_op<T>(
+        &mut self,
+        driver: Driver,
+        op: T,
+        sqe: Entry,
+        bgid: u16,
+    ) -> Result<Op<T>, (io::Error, T)> {
         let key = self.ops.insert(Lifecycle::Submitted);
-        let sqe = sqe.user_data(key as u64);
-        self.submit(sqe)?;
+        let user_data = (bgid as u64) << 32 | key as u64;
+        if let Err(e) = self.push(sqe.user_data(user_data)) {
+            self.ops.remove(key);
+            return Err((e, op));
+        }
         Ok(Op {
             driver,
             op: Some(op),
             key,
+            user_data,
         })
     }
 }
 
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Unregister the buffer rings while the io_uring instance is still open, the `Buf`s handed
+        // out may keep them alive past it.
+        let fd = self.ring.as_raw_fd();
+        let _ = self.buf_ring.unregister(fd);
+        let _ = self.dgram_buf_ring.unregister(fd);
+    }
+}
+
 impl Driver {
-    pub(crate) fn new() -> io::Result<Driver> {
+    pub(crate) fn new(huge_pages: HugePages) -> io::Result<Driver> {
         Ok(Driver {
-            inner: Rc::new(RefCell::new(Inner::new()?)),
+            inner: Rc::new(RefCell::new(Inner::new(huge_pages)?)),
         })
     }
 
@@ -174,8 +217,70 @@ impl Driver {
         CURRENT.set(self, f)
     }
 
-    pub(crate) fn submit<T>(&self, op: T, sqe: Entry) -> io::Result<Op<T>> {
-        self.inner.borrow_mut().submit_op(self.clone(), op, sqe)
+    pub(crate) fn submit<T>(&self, op: T, sqe: Entry, bgid: u16) -> io::Result<Op<T>> {
+        self.try_submit(op, sqe, bgid).map_err(|(e, _)| e)
+    }
+
+    // Like `submit`, handing the op back along with the error if it cannot be submitted.
+    pub(crate) fn try_submit<T>(
+        &self,
+        op: T,
+        sqe: Entry,
+        bgid: u16,
+    ) -> Result<Op<T>, (io::Error, T)> {
+        self.inner
+            .borrow_mut()
+            .submit_op(self.clone(), op, sqe, bgid)
+    }
+
+    // Submit a batch of ops with a single flush of the submission queue, their entries
+    // contiguous in it so that links between them hold. The batch must fit in the queue,
+    // which is flushed first if it lacks room. Nothing is pushed unless the whole batch fits,
+    // so that pushing does not flush the queue midway, leaving a link to whatever entry
+    // comes next.
+    pub(crate) fn submit_many<T>(&self, ops: Vec<(T, Entry)>, bgid: u16) -> io::Result<Vec<Op<T>>> {
+        let mut inner = self.inner.borrow_mut();
+        assert!(
+            ops.len() <= inner.ring.submission().capacity(),
+            "batch larger than the submission queue"
+        );
+        if ops.len() > inner.free_entries() {
+            inner.ring.submit()?;
+            if ops.len() > inner.free_entries() {
+                return Err(io::Error::from_raw_os_error(libc::EBUSY));
+            }
+        }
+        let mut submitted = Vec::with_capacity(ops.len());
+        let mut res = Ok(0);
+        for (op, sqe) in ops {
+            match inner.push_op(self.clone(), op, sqe, bgid) {
+                Ok(op) => submitted.push(op),
+                Err((e, _)) => {
+                    res = Err(e);
+                    break;
+                }
+            }
+        }
+        if res.is_ok() {
+            res = inner.ring.submit();
+        }
+        // On failure the ops already pushed are cancelled as they drop, which borrows the
+        // driver.
+        drop(inner);
+        res.map(|_| submitted)
+    }
+}
+
+/// Install `fd` in the registered file table of the current driver, returning its index.
+pub(crate) fn register_file(fd: RawFd) -> io::Result<u32> {
+    CURRENT.with(|driver| driver.inner.borrow_mut().register_file(fd))
+}
+
+/// Clear the slot `index` of the registered file table. Without a current driver the table
+/// is already gone, along with the files it held.
+pub(crate) fn unregister_file(index: u32) {
+    if CURRENT.is_set() {
+        CURRENT.with(|driver| driver.inner.borrow_mut().unregister_file(index));
     }
 }
 
@@ -188,7 +293,8 @@ enum Lifecycle {
     Completed(CqeResult),
     /// The operations list.
     CompletionList(Vec<CqeResult>),
-    /// Ignored
+    /// Ignored, the op is kept until its last cqe, which for zero-copy sends is the
+    /// notification that the kernel released the buffer.
     #[allow(dead_code)]
     Ignored(Box<dyn Any>),
 }
@@ -197,12 +303,13 @@ impl Lifecycle {
     fn complete(&mut self, entry: cqueue::Entry, buf_ring: &BufRing) -> bool {
         let mut cqe: CqeResult = entry.into();
         if let Some(bid) = cqueue::buffer_select(cqe.flags) {
+            let more = buffer::buf_more(cqe.flags);
             match cqe.result {
                 Ok(len) => {
-                    cqe.buf = Some(buf_ring.get_buf(len as usize, bid));
+                    cqe.buf = Some(buf_ring.get_buf(len as usize, bid, more));
                 }
                 Err(_) => {
-                    buf_ring.drop_buf(bid);
+                    buf_ring.drop_buf(bid, more);
                 }
             }
         }
@@ -241,7 +348,8 @@ pub(crate) trait Completable {
     type Output;
     /// `complete` will be called for cqe's do not have the `more` flag set
     fn complete(self, cqe: CqeResult) -> Self::Output;
-    /// Update will be called for cqe's which have the `more` flag set.
+    /// Update will be called for cqe's which have the `more` flag set, the results of
+    /// multishot operations, or of zero-copy sends whose notification is still to come.
     /// The Op should update any internal state as required.
     fn update(&mut self, _cqe: CqeResult) {}
 }
@@ -250,6 +358,7 @@ pub(crate) struct Op<T: 'static> {
     pub driver: Driver,
     pub op: Option<T>,
     pub key: usize,
+    pub user_data: u64,
 }
 
 impl<T> Op<T> {
@@ -258,7 +367,21 @@ impl<T> Op<T> {
     }
 
     pub(crate) fn submit(op: T, entry: Entry) -> io::Result<Op<T>> {
-        CURRENT.with(|driver| driver.submit(op, entry))
+        CURRENT.with(|driver| driver.submit(op, entry, BUF_BGID))
+    }
+
+    // Submit an op owning a buffer of the caller, which is handed back if submission fails.
+    pub(crate) fn try_submit(op: T, entry: Entry) -> Result<Op<T>, (io::Error, T)> {
+        CURRENT.with(|driver| driver.try_submit(op, entry, BUF_BGID))
+    }
+
+    // Submit an op selecting its buffers from the datagram buffer group.
+    pub(crate) fn submit_dgram(op: T, entry: Entry) -> io::Result<Op<T>> {
+        CURRENT.with(|driver| driver.submit(op, entry, DGRAM_BUF_BGID))
+    }
+
+    pub(crate) fn submit_many(ops: Vec<(T, Entry)>) -> io::Result<Vec<Op<T>>> {
+        CURRENT.with(|driver| driver.submit_many(ops, BUF_BGID))
     }
 
     pub(crate) fn reset(&self, waker: Waker) {
@@ -357,7 +480,7 @@ impl<T> Drop for Op<T> {
             Lifecycle::Ignored(..) => unreachable!(),
         }
         if !finished {
-            let sqe = opcode::AsyncCancel::new(self.key as u64)
+            let sqe = opcode::AsyncCancel::new(self.user_data)
                 .build()
                 .user_data(u64::MAX);
             let _ = inner.submit(sqe);
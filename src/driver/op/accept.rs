@@ -1,30 +1,38 @@
 use std::io;
 use std::mem;
-use std::os::unix::io::RawFd;
 
 use io_uring::{opcode, types};
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 use crate::socket::Socket;
 
 pub(crate) struct Accept {
     pub(crate) socketaddr: Box<(libc::sockaddr_storage, libc::socklen_t)>,
+    // Accept into a slot of the registered file table allocated by the kernel, instead of a
+    // file descriptor.
+    direct: bool,
 }
 
 impl Op<Accept> {
-    pub(crate) fn accept(fd: RawFd) -> io::Result<Op<Accept>> {
+    pub(crate) fn accept(fd: Fd, direct: bool) -> io::Result<Op<Accept>> {
         let mut socketaddr = Box::new((
             unsafe { mem::zeroed() },
             mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t,
         ));
-        let entry = opcode::Accept::new(
-            types::Fd(fd),
-            &mut socketaddr.0 as *mut _ as *mut _,
-            &mut socketaddr.1,
-        )
-        .flags(libc::SOCK_CLOEXEC)
-        .build();
-        Op::submit(Accept { socketaddr }, entry)
+        // Direct descriptors have no file descriptor to close on exec, the kernel rejects the
+        // flag for them.
+        let (file_index, flags) = if direct {
+            (Some(types::DestinationSlot::auto_target()), 0)
+        } else {
+            (None, libc::SOCK_CLOEXEC)
+        };
+        let entry = fd.build(|fd| {
+            opcode::Accept::new(fd, &mut socketaddr.0 as *mut _ as *mut _, &mut socketaddr.1)
+                .file_index(file_index)
+                .flags(flags)
+                .build()
+        });
+        Op::submit(Accept { socketaddr, direct }, entry)
     }
 }
 
@@ -32,7 +40,12 @@ impl Completable for Accept {
     type Output = io::Result<(Socket, Box<(libc::sockaddr_storage, libc::socklen_t)>)>;
 
     fn complete(self, cqe: CqeResult) -> Self::Output {
-        let fd = cqe.result? as i32;
-        Ok((Socket::from(fd), self.socketaddr))
+        let res = cqe.result?;
+        let socket = if self.direct {
+            Socket::from_fixed(res)
+        } else {
+            Socket::from(res as i32)
+        };
+        Ok((socket, self.socketaddr))
     }
 }
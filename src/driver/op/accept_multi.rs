@@ -1,10 +1,9 @@
 use std::collections::VecDeque;
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 
 pub(crate) struct AcceptMulti {
     results: VecDeque<CqeResult>,
@@ -17,10 +16,12 @@ impl AcceptMulti {
 }
 
 impl Op<AcceptMulti> {
-    pub(crate) fn accept_multi(fd: RawFd) -> io::Result<Op<AcceptMulti>> {
-        let entry = opcode::AcceptMulti::new(types::Fd(fd))
-            .flags(libc::SOCK_CLOEXEC)
-            .build();
+    pub(crate) fn accept_multi(fd: Fd) -> io::Result<Op<AcceptMulti>> {
+        let entry = fd.build(|fd| {
+            opcode::AcceptMulti::new(fd)
+                .flags(libc::SOCK_CLOEXEC)
+                .build()
+        });
         Op::submit(
             AcceptMulti {
                 results: VecDeque::new(),
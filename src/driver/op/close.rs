@@ -0,0 +1,24 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Close;
+
+impl Op<Close> {
+    pub(crate) fn close(fd: RawFd) -> io::Result<Op<Close>> {
+        let entry = opcode::Close::new(types::Fd(fd)).build();
+        Op::submit(Close, entry)
+    }
+}
+
+impl Completable for Close {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
@@ -1,24 +1,20 @@
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 use socket2::SockAddr;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 
 pub(crate) struct Connect {
     sock_addr: SockAddr,
 }
 
 impl Op<Connect> {
-    pub(crate) fn connect(fd: RawFd, sock_addr: SockAddr) -> io::Result<Op<Connect>> {
+    pub(crate) fn connect(fd: Fd, sock_addr: SockAddr) -> io::Result<Op<Connect>> {
         let connect = Connect { sock_addr };
-        let entry = opcode::Connect::new(
-            types::Fd(fd),
-            connect.sock_addr.as_ptr(),
-            connect.sock_addr.len(),
-        )
-        .build();
+        let entry = fd.build(|fd| {
+            opcode::Connect::new(fd, connect.sock_addr.as_ptr(), connect.sock_addr.len()).build()
+        });
         Op::submit(connect, entry)
     }
 }
@@ -0,0 +1,28 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+pub(crate) struct Fadvise;
+
+impl Op<Fadvise> {
+    pub(crate) fn fadvise(
+        fd: Fd,
+        offset: u64,
+        len: libc::off_t,
+        advice: i32,
+    ) -> io::Result<Op<Fadvise>> {
+        let entry = fd.build(|fd| opcode::Fadvise::new(fd, len, advice).offset(offset).build());
+        Op::submit(Fadvise, entry)
+    }
+}
+
+impl Completable for Fadvise {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
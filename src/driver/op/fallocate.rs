@@ -0,0 +1,28 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+pub(crate) struct Fallocate;
+
+impl Op<Fallocate> {
+    pub(crate) fn fallocate(fd: Fd, offset: u64, len: u64, mode: i32) -> io::Result<Op<Fallocate>> {
+        let entry = fd.build(|fd| {
+            opcode::Fallocate::new(fd, len)
+                .offset(offset)
+                .mode(mode)
+                .build()
+        });
+        Op::submit(Fallocate, entry)
+    }
+}
+
+impl Completable for Fallocate {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
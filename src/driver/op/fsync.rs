@@ -0,0 +1,28 @@
+use std::io;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+pub(crate) struct Fsync;
+
+impl Op<Fsync> {
+    pub(crate) fn fsync(fd: Fd, data_only: bool) -> io::Result<Op<Fsync>> {
+        let flags = if data_only {
+            types::FsyncFlags::DATASYNC
+        } else {
+            types::FsyncFlags::empty()
+        };
+        let entry = fd.build(|fd| opcode::Fsync::new(fd).flags(flags).build());
+        Op::submit(Fsync, entry)
+    }
+}
+
+impl Completable for Fsync {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+pub(crate) struct Ftruncate;
+
+impl Op<Ftruncate> {
+    pub(crate) fn ftruncate(fd: Fd, len: u64) -> io::Result<Op<Ftruncate>> {
+        let entry = fd.build(|fd| opcode::Ftruncate::new(fd, len).build());
+        Op::submit(Ftruncate, entry)
+    }
+}
+
+impl Completable for Ftruncate {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::ffi::CString;
+use std::io;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Link {
+    original: CString,
+    link: CString,
+}
+
+impl Op<Link> {
+    pub(crate) fn hard_link(original: CString, link: CString) -> io::Result<Op<Link>> {
+        let hard_link = Link { original, link };
+        let entry = opcode::LinkAt::new(
+            types::Fd(libc::AT_FDCWD),
+            hard_link.original.as_ptr(),
+            types::Fd(libc::AT_FDCWD),
+            hard_link.link.as_ptr(),
+        )
+        .build();
+        Op::submit(hard_link, entry)
+    }
+
+    pub(crate) fn symlink(original: CString, link: CString) -> io::Result<Op<Link>> {
+        let symlink = Link { original, link };
+        let entry = opcode::SymlinkAt::new(
+            types::Fd(libc::AT_FDCWD),
+            symlink.original.as_ptr(),
+            symlink.link.as_ptr(),
+        )
+        .build();
+        Op::submit(symlink, entry)
+    }
+}
+
+impl Completable for Link {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
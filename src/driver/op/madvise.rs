@@ -0,0 +1,27 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Madvise;
+
+impl Op<Madvise> {
+    pub(crate) fn madvise(
+        addr: *const libc::c_void,
+        len: libc::off_t,
+        advice: i32,
+    ) -> io::Result<Op<Madvise>> {
+        let entry = opcode::Madvise::new(addr, len, advice).build();
+        Op::submit(Madvise, entry)
+    }
+}
+
+impl Completable for Madvise {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
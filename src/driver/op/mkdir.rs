@@ -0,0 +1,29 @@
+use std::ffi::CString;
+use std::io;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Mkdir {
+    path: CString,
+}
+
+impl Op<Mkdir> {
+    pub(crate) fn mkdir(path: CString, mode: libc::mode_t) -> io::Result<Op<Mkdir>> {
+        let mkdir = Mkdir { path };
+        let entry = opcode::MkDirAt::new(types::Fd(libc::AT_FDCWD), mkdir.path.as_ptr())
+            .mode(mode)
+            .build();
+        Op::submit(mkdir, entry)
+    }
+}
+
+impl Completable for Mkdir {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
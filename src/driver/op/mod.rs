@@ -1,25 +1,49 @@
 mod accept;
 mod accept_multi;
+mod close;
 mod connect;
+mod fadvise;
+mod fallocate;
+mod fsync;
+mod ftruncate;
+mod link;
+mod madvise;
+mod mkdir;
+mod open;
 mod read;
+mod read_at;
 mod recv;
 mod recv_multi;
 mod recvmsg;
+mod recvmsg_multi;
+mod rename;
 mod send;
+mod send_many;
+mod send_zc;
 mod sendmsg;
 mod shutdown;
+mod splice;
+mod statx;
+mod sync_file_range;
+mod tee;
 mod timeout;
+mod unlink;
 mod write;
+mod write_at;
 
 pub(crate) use accept::Accept;
 pub(crate) use accept_multi::AcceptMulti;
 pub(crate) use connect::Connect;
 pub(crate) use read::Read;
+pub(crate) use read_at::ReadAt;
 pub(crate) use recv::Recv;
 pub(crate) use recv_multi::RecvMulti;
 pub(crate) use recvmsg::RecvMsg;
+pub(crate) use recvmsg_multi::RecvMsgMulti;
 pub(crate) use send::Send;
 pub(crate) use sendmsg::SendMsg;
 pub(crate) use shutdown::Shutdown;
+pub(crate) use statx::Statx;
 pub(crate) use timeout::Timeout;
 pub(crate) use write::Write;
+pub(crate) use write_at::WriteAt;
@@ -0,0 +1,34 @@
+use std::ffi::CString;
+use std::io;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Open {
+    path: CString,
+}
+
+impl Op<Open> {
+    pub(crate) fn open(
+        path: CString,
+        flags: libc::c_int,
+        mode: libc::mode_t,
+    ) -> io::Result<Op<Open>> {
+        let open = Open { path };
+        let entry = opcode::OpenAt::new(types::Fd(libc::AT_FDCWD), open.path.as_ptr())
+            .flags(flags | libc::O_CLOEXEC)
+            .mode(mode)
+            .build();
+        Op::submit(open, entry)
+    }
+}
+
+impl Completable for Open {
+    type Output = io::Result<i32>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        let fd = cqe.result? as i32;
+        Ok(fd)
+    }
+}
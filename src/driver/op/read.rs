@@ -1,19 +1,20 @@
 use std::io;
-use std::os::unix::io::RawFd;
 use std::ptr;
 
-use io_uring::{opcode, squeue, types};
+use io_uring::{opcode, squeue};
 
-use crate::driver::{Buf, Completable, CqeResult, Op, BUF_BGID};
+use crate::driver::{Buf, Completable, CqeResult, Fd, Op, BUF_BGID};
 
 pub(crate) struct Read;
 
 impl Op<Read> {
-    pub(crate) fn read(fd: RawFd, len: u32) -> io::Result<Op<Read>> {
-        let entry = opcode::Read::new(types::Fd(fd), ptr::null_mut(), len)
-            .buf_group(BUF_BGID)
-            .build()
-            .flags(squeue::Flags::BUFFER_SELECT);
+    pub(crate) fn read(fd: Fd, len: u32) -> io::Result<Op<Read>> {
+        let entry = fd.build(|fd| {
+            opcode::Read::new(fd, ptr::null_mut(), len)
+                .buf_group(BUF_BGID)
+                .build()
+                .flags(squeue::Flags::BUFFER_SELECT)
+        });
         Op::submit(Read, entry)
     }
 }
@@ -25,7 +26,7 @@ impl Completable for Read {
         let _ = cqe.result?;
         match cqe.buf {
             Some(buf) => Ok(buf),
-            None => Err(io::Error::new(io::ErrorKind::Other, "buf not found")),
+            None => Err(io::Error::other("buf not found")),
         }
     }
 }
@@ -0,0 +1,37 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+use crate::fs::AlignedBuf;
+
+pub(crate) struct ReadAt {
+    buf: AlignedBuf,
+}
+
+impl Op<ReadAt> {
+    // Read up to the capacity of `buf`, replacing its contents. The buffer is handed back
+    // whatever the outcome, empty unless the read succeeds.
+    pub(crate) fn read_at(
+        fd: Fd,
+        mut buf: AlignedBuf,
+        offset: u64,
+    ) -> Result<Op<ReadAt>, (io::Error, AlignedBuf)> {
+        let entry = fd.build(|fd| {
+            opcode::Read::new(fd, buf.as_mut_ptr(), buf.capacity() as u32)
+                .offset(offset)
+                .build()
+        });
+        Op::try_submit(ReadAt { buf }, entry).map_err(|(e, read)| (e, read.buf))
+    }
+}
+
+impl Completable for ReadAt {
+    type Output = (io::Result<usize>, AlignedBuf);
+
+    fn complete(mut self, cqe: CqeResult) -> Self::Output {
+        let res = cqe.result.map(|n| n as usize);
+        unsafe { self.buf.set_len(*res.as_ref().unwrap_or(&0)) };
+        (res, self.buf)
+    }
+}
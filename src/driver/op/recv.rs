@@ -1,18 +1,17 @@
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 
 pub(crate) struct Recv {
     buf: Vec<u8>,
 }
 
 impl Op<Recv> {
-    pub(crate) fn recv(fd: RawFd, len: usize) -> io::Result<Op<Recv>> {
+    pub(crate) fn recv(fd: Fd, len: usize) -> io::Result<Op<Recv>> {
         let mut buf = Vec::with_capacity(len);
-        let entry = opcode::Recv::new(types::Fd(fd), buf.as_mut_ptr(), len as u32).build();
+        let entry = fd.build(|fd| opcode::Recv::new(fd, buf.as_mut_ptr(), len as u32).build());
         Op::submit(Recv { buf }, entry)
     }
 }
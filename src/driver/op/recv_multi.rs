@@ -1,10 +1,9 @@
 use std::collections::VecDeque;
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 
-use crate::driver::{Buf, Completable, CqeResult, Op, BUF_BGID};
+use crate::driver::{Buf, Completable, CqeResult, Fd, Op, DGRAM_BUF_BGID};
 
 pub(crate) struct RecvMulti {
     results: VecDeque<io::Result<Buf>>,
@@ -17,9 +16,9 @@ impl RecvMulti {
 }
 
 impl Op<RecvMulti> {
-    pub(crate) fn recv_multi(fd: RawFd) -> io::Result<Op<RecvMulti>> {
-        let entry = opcode::RecvMulti::new(types::Fd(fd), BUF_BGID).build();
-        Op::submit(
+    pub(crate) fn recv_multi(fd: Fd) -> io::Result<Op<RecvMulti>> {
+        let entry = fd.build(|fd| opcode::RecvMulti::new(fd, DGRAM_BUF_BGID).build());
+        Op::submit_dgram(
             RecvMulti {
                 results: VecDeque::new(),
             },
@@ -35,14 +34,14 @@ impl Completable for RecvMulti {
         let _ = cqe.result?;
         match cqe.buf {
             Some(buf) => Ok(buf),
-            None => Err(io::Error::new(io::ErrorKind::Other, "buf not found")),
+            None => Err(io::Error::other("buf not found")),
         }
     }
 
     fn update(&mut self, cqe: CqeResult) {
         let buf = cqe.result.and_then(|_| match cqe.buf {
             Some(buf) => Ok(buf),
-            None => Err(io::Error::new(io::ErrorKind::Other, "buf not found")),
+            None => Err(io::Error::other("buf not found")),
         });
         self.results.push_back(buf);
     }
@@ -1,11 +1,11 @@
 use std::io::{self, IoSliceMut};
 use std::net::SocketAddr;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 use socket2::SockAddr;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
+use crate::socket::cmsg::Cmsgs;
 
 #[allow(dead_code)]
 pub(crate) struct RecvMsg {
@@ -13,10 +13,15 @@ pub(crate) struct RecvMsg {
     io_slices: Vec<IoSliceMut<'static>>,
     buf: Vec<u8>,
     msghdr: Box<libc::msghdr>,
+    control: Option<Cmsgs>,
 }
 
 impl Op<RecvMsg> {
-    pub(crate) fn recvmsg(fd: RawFd, len: usize) -> io::Result<Op<RecvMsg>> {
+    pub(crate) fn recvmsg(
+        fd: Fd,
+        len: usize,
+        mut control: Option<Cmsgs>,
+    ) -> io::Result<Op<RecvMsg>> {
         let mut buf = Vec::with_capacity(len);
         let mut io_slices = vec![IoSliceMut::new(unsafe {
             std::slice::from_raw_parts_mut(buf.as_mut_ptr(), len)
@@ -27,19 +32,25 @@ impl Op<RecvMsg> {
         msghdr.msg_iovlen = io_slices.len() as _;
         msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
         msghdr.msg_namelen = socket_addr.len();
+        if let Some(control) = &mut control {
+            msghdr.msg_control = control.as_mut_ptr();
+            msghdr.msg_controllen = control.capacity() as _;
+        }
         let mut recv_msg = RecvMsg {
             socket_addr,
             buf,
             msghdr,
             io_slices,
+            control,
         };
-        let entry = opcode::RecvMsg::new(types::Fd(fd), recv_msg.msghdr.as_mut() as *mut _).build();
+        let entry =
+            fd.build(|fd| opcode::RecvMsg::new(fd, recv_msg.msghdr.as_mut() as *mut _).build());
         Op::submit(recv_msg, entry)
     }
 }
 
 impl Completable for RecvMsg {
-    type Output = io::Result<(Vec<u8>, SocketAddr)>;
+    type Output = io::Result<(Vec<u8>, SocketAddr, Option<Cmsgs>)>;
 
     fn complete(mut self, cqe: CqeResult) -> Self::Output {
         let n = cqe.result? as usize;
@@ -48,6 +59,9 @@ impl Completable for RecvMsg {
             .socket_addr
             .as_socket()
             .ok_or(io::ErrorKind::InvalidInput)?;
-        Ok((self.buf, socket_addr))
+        if let Some(control) = &mut self.control {
+            unsafe { control.set_len(self.msghdr.msg_controllen) };
+        }
+        Ok((self.buf, socket_addr, self.control))
     }
 }
@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+
+use io_uring::{opcode, types};
+use socket2::SockAddr;
+
+use crate::buffer::Buf;
+use crate::driver::{Completable, CqeResult, Fd, Op, DGRAM_BUF_BGID};
+
+// The outer error is a failure of the receive, which ends it, the inner one a failure of a
+// single datagram.
+type Datagram = io::Result<io::Result<(Buf, SocketAddr)>>;
+
+pub(crate) struct RecvMsgMulti {
+    msghdr: Box<libc::msghdr>,
+    results: VecDeque<Datagram>,
+}
+
+impl RecvMsgMulti {
+    pub fn next(&mut self) -> Option<Datagram> {
+        self.results.pop_front()
+    }
+
+    // Every buffer starts with an `io_uring_recvmsg_out` header, followed by the source address
+    // in a field of `msg_namelen` bytes, then the payload.
+    fn parse(&self, cqe: CqeResult) -> Datagram {
+        cqe.result?;
+        let mut buf = cqe.buf.ok_or_else(|| io::Error::other("buf not found"))?;
+        let out = types::RecvMsgOut::parse(&buf, &self.msghdr)
+            .map_err(|_| io::Error::other("invalid recvmsg header"))?;
+        if out.is_payload_truncated() {
+            return Ok(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "datagram larger than the buffer, truncated",
+            )));
+        }
+        let name = out.name_data();
+        let (_, addr) = unsafe {
+            SockAddr::try_init(|storage, len| {
+                std::ptr::copy_nonoverlapping(name.as_ptr(), storage.cast(), name.len());
+                *len = name.len() as libc::socklen_t;
+                Ok(())
+            })?
+        };
+        let Some(addr) = addr.as_socket() else {
+            return Ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        let payload = out.payload_data();
+        let start = payload.as_ptr() as usize - buf.as_ptr() as usize;
+        let len = payload.len();
+        buf.narrow(start..start + len);
+        Ok(Ok((buf, addr)))
+    }
+}
+
+impl Op<RecvMsgMulti> {
+    pub(crate) fn recvmsg_multi(fd: Fd) -> io::Result<Op<RecvMsgMulti>> {
+        let mut msghdr: Box<libc::msghdr> = Box::new(unsafe { mem::zeroed() });
+        msghdr.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+        let entry = fd.build(|fd| {
+            opcode::RecvMsgMulti::new(fd, msghdr.as_ref() as *const _, DGRAM_BUF_BGID).build()
+        });
+        Op::submit_dgram(
+            RecvMsgMulti {
+                msghdr,
+                results: VecDeque::new(),
+            },
+            entry,
+        )
+    }
+}
+
+impl Completable for RecvMsgMulti {
+    type Output = Datagram;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        self.parse(cqe)
+    }
+
+    fn update(&mut self, cqe: CqeResult) {
+        let res = self.parse(cqe);
+        self.results.push_back(res);
+    }
+}
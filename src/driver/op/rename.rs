@@ -0,0 +1,34 @@
+use std::ffi::CString;
+use std::io;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Rename {
+    from: CString,
+    to: CString,
+}
+
+impl Op<Rename> {
+    pub(crate) fn rename(from: CString, to: CString) -> io::Result<Op<Rename>> {
+        let rename = Rename { from, to };
+        let entry = opcode::RenameAt::new(
+            types::Fd(libc::AT_FDCWD),
+            rename.from.as_ptr(),
+            types::Fd(libc::AT_FDCWD),
+            rename.to.as_ptr(),
+        )
+        .build();
+        Op::submit(rename, entry)
+    }
+}
+
+impl Completable for Rename {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
@@ -1,9 +1,8 @@
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 
 #[allow(dead_code)]
 pub(crate) struct Send {
@@ -11,9 +10,9 @@ pub(crate) struct Send {
 }
 
 impl Op<Send> {
-    pub(crate) fn send(fd: RawFd, buf: &[u8]) -> io::Result<Op<Send>> {
+    pub(crate) fn send(fd: Fd, buf: &[u8]) -> io::Result<Op<Send>> {
         let buf = buf.to_vec();
-        let entry = opcode::Send::new(types::Fd(fd), buf.as_ptr(), buf.len() as u32).build();
+        let entry = fd.build(|fd| opcode::Send::new(fd, buf.as_ptr(), buf.len() as u32).build());
         Op::submit(Send { buf }, entry)
     }
 }
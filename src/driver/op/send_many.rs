@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::io;
+use std::mem;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+use io_uring::{opcode, squeue};
+use socket2::SockAddr;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+// The most batches kept for reuse per thread.
+const POOL_LEN: usize = 8;
+
+thread_local! {
+    static POOL: RefCell<Vec<Batch>> = const { RefCell::new(Vec::new()) };
+}
+
+// The payloads, addresses and headers of a batch of datagrams, in a few allocations shared by
+// the sends of the batch instead of some per send. The last send to complete recycles it.
+#[derive(Default)]
+struct Batch {
+    data: Vec<u8>,
+    addrs: Vec<SockAddr>,
+    iovecs: Vec<libc::iovec>,
+    msghdrs: Vec<libc::msghdr>,
+}
+
+impl Batch {
+    fn take() -> Batch {
+        POOL.with(|pool| pool.borrow_mut().pop())
+            .unwrap_or_default()
+    }
+
+    fn recycle(mut self) {
+        self.data.clear();
+        self.addrs.clear();
+        self.iovecs.clear();
+        self.msghdrs.clear();
+        POOL.with(|pool| {
+            let mut pool = pool.borrow_mut();
+            if pool.len() < POOL_LEN {
+                pool.push(self);
+            }
+        });
+    }
+}
+
+// One datagram of a batch, sent with a `sendmsg` linked to the one of the next datagram, so
+// that the datagrams go out in order and the first failure cancels the rest, like `sendmmsg`.
+// Bundled sends are of no use here, they take a single destination.
+pub(crate) struct SendMany {
+    batch: Rc<Batch>,
+}
+
+impl Op<SendMany> {
+    pub(crate) fn send_many(fd: Fd, bufs: &[(&[u8], SocketAddr)]) -> io::Result<Vec<Op<SendMany>>> {
+        let mut batch = Batch::take();
+        for (buf, addr) in bufs {
+            batch.data.extend_from_slice(buf);
+            batch.addrs.push(SockAddr::from(*addr));
+        }
+        // The vectors are complete, pointers into them stay valid.
+        let mut base = batch.data.as_mut_ptr();
+        for (buf, _) in bufs {
+            batch.iovecs.push(libc::iovec {
+                iov_base: base.cast(),
+                iov_len: buf.len(),
+            });
+            base = unsafe { base.add(buf.len()) };
+        }
+        for i in 0..bufs.len() {
+            let mut msghdr: libc::msghdr = unsafe { mem::zeroed() };
+            msghdr.msg_iov = &mut batch.iovecs[i];
+            msghdr.msg_iovlen = 1;
+            msghdr.msg_name = batch.addrs[i].as_ptr() as *mut libc::c_void;
+            msghdr.msg_namelen = batch.addrs[i].len();
+            batch.msghdrs.push(msghdr);
+        }
+
+        let last = bufs.len().saturating_sub(1);
+        let entries = batch
+            .msghdrs
+            .iter()
+            .enumerate()
+            .map(|(i, msghdr)| {
+                let entry = fd.build(|fd| opcode::SendMsg::new(fd, msghdr).build());
+                if i < last {
+                    entry.flags(squeue::Flags::IO_LINK)
+                } else {
+                    entry
+                }
+            })
+            .collect::<Vec<_>>();
+        let batch = Rc::new(batch);
+        let ops = entries
+            .into_iter()
+            .map(|entry| {
+                let send = SendMany {
+                    batch: batch.clone(),
+                };
+                (send, entry)
+            })
+            .collect();
+        Op::submit_many(ops)
+    }
+}
+
+impl Completable for SendMany {
+    type Output = io::Result<usize>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        if let Some(batch) = Rc::into_inner(self.batch) {
+            batch.recycle();
+        }
+        let n = cqe.result? as usize;
+        Ok(n)
+    }
+}
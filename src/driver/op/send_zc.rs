@@ -0,0 +1,56 @@
+use std::io;
+use std::net::SocketAddr;
+
+use io_uring::{cqueue, opcode};
+use socket2::SockAddr;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+// The kernel posts the result of the send first, flagged with `more`, then a notification
+// once it no longer uses `buf`. The send result is kept until the notification arrives.
+pub(crate) struct SendZc {
+    buf: Vec<u8>,
+    #[allow(dead_code)]
+    socket_addr: Option<Box<SockAddr>>,
+    result: Option<io::Result<u32>>,
+}
+
+impl Op<SendZc> {
+    pub(crate) fn send_zc(
+        fd: Fd,
+        buf: Vec<u8>,
+        socket_addr: Option<SocketAddr>,
+    ) -> io::Result<Op<SendZc>> {
+        let socket_addr = socket_addr.map(|addr| Box::new(SockAddr::from(addr)));
+        let entry = fd.build(|fd| {
+            let send = opcode::SendZc::new(fd, buf.as_ptr(), buf.len() as u32);
+            match &socket_addr {
+                Some(addr) => send.dest_addr(addr.as_ptr()).dest_addr_len(addr.len()),
+                None => send,
+            }
+            .build()
+        });
+        let send_zc = SendZc {
+            buf,
+            socket_addr,
+            result: None,
+        };
+        Op::submit(send_zc, entry)
+    }
+}
+
+impl Completable for SendZc {
+    type Output = io::Result<(usize, Vec<u8>)>;
+
+    fn complete(mut self, cqe: CqeResult) -> Self::Output {
+        let result = match self.result.take() {
+            Some(result) if cqueue::notif(cqe.flags) => result,
+            _ => cqe.result,
+        };
+        Ok((result? as usize, self.buf))
+    }
+
+    fn update(&mut self, cqe: CqeResult) {
+        self.result = Some(cqe.result);
+    }
+}
@@ -1,11 +1,11 @@
 use std::io::{self, IoSliceMut};
 use std::net::SocketAddr;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 use socket2::SockAddr;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
+use crate::socket::cmsg::Cmsgs;
 
 #[allow(dead_code)]
 pub(crate) struct SendMsg {
@@ -13,13 +13,15 @@ pub(crate) struct SendMsg {
     buf: Vec<u8>,
     io_slices: Vec<IoSliceMut<'static>>,
     msghdr: Box<libc::msghdr>,
+    control: Option<Cmsgs>,
 }
 
 impl Op<SendMsg> {
     pub(crate) fn sendmsg(
-        fd: RawFd,
+        fd: Fd,
         buf: &[u8],
         socket_addr: SocketAddr,
+        mut control: Option<Cmsgs>,
     ) -> io::Result<Op<SendMsg>> {
         let len = buf.len();
         let mut buf = buf.to_vec();
@@ -32,13 +34,19 @@ impl Op<SendMsg> {
         msghdr.msg_iovlen = io_slices.len() as _;
         msghdr.msg_name = socket_addr.as_ptr() as *mut libc::c_void;
         msghdr.msg_namelen = socket_addr.len();
+        if let Some(control) = &mut control {
+            msghdr.msg_control = control.as_mut_ptr();
+            msghdr.msg_controllen = control.len() as _;
+        }
         let mut send_msg = SendMsg {
             buf,
             msghdr,
             socket_addr,
             io_slices,
+            control,
         };
-        let entry = opcode::SendMsg::new(types::Fd(fd), send_msg.msghdr.as_mut() as *mut _).build();
+        let entry =
+            fd.build(|fd| opcode::SendMsg::new(fd, send_msg.msghdr.as_mut() as *mut _).build());
         Op::submit(send_msg, entry)
     }
 }
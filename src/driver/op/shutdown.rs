@@ -1,16 +1,15 @@
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 
 pub(crate) struct Shutdown;
 
 impl Op<Shutdown> {
-    pub(crate) fn shutdown(fd: RawFd, how: libc::c_int) -> io::Result<Op<Shutdown>> {
+    pub(crate) fn shutdown(fd: Fd, how: libc::c_int) -> io::Result<Op<Shutdown>> {
         let shutdown = Shutdown;
-        let entry = opcode::Shutdown::new(types::Fd(fd), how).build();
+        let entry = fd.build(|fd| opcode::Shutdown::new(fd, how).build());
         Op::submit(shutdown, entry)
     }
 }
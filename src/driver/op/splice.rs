@@ -0,0 +1,45 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+// Not exposed by libc.
+const SPLICE_F_FD_IN_FIXED: u32 = 1 << 31;
+
+pub(crate) struct Splice;
+
+impl Op<Splice> {
+    pub(crate) fn splice(
+        fd_in: Fd,
+        off_in: i64,
+        fd_out: Fd,
+        off_out: i64,
+        len: u32,
+    ) -> io::Result<Op<Splice>> {
+        // The input file of a splice is marked as registered with a flag of its own.
+        let (fd_in, flags) = match fd_in {
+            Fd::Raw(fd) => (types::Fd(fd), libc::SPLICE_F_MOVE),
+            Fd::Fixed(index) => (
+                types::Fd(index as RawFd),
+                libc::SPLICE_F_MOVE | SPLICE_F_FD_IN_FIXED,
+            ),
+        };
+        let entry = fd_out.build(|fd_out| {
+            opcode::Splice::new(fd_in, off_in, fd_out, off_out, len)
+                .flags(flags)
+                .build()
+        });
+        Op::submit(Splice, entry)
+    }
+}
+
+impl Completable for Splice {
+    type Output = io::Result<usize>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        let n = cqe.result? as usize;
+        Ok(n)
+    }
+}
@@ -0,0 +1,50 @@
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Statx {
+    path: CString,
+    statx: Box<libc::statx>,
+}
+
+impl Op<Statx> {
+    // Stat the file open as `fd`.
+    pub(crate) fn statx(fd: RawFd, mask: libc::c_uint) -> io::Result<Op<Statx>> {
+        Op::statx_at(fd, CString::default(), libc::AT_EMPTY_PATH, mask)
+    }
+
+    pub(crate) fn statx_at(
+        dirfd: RawFd,
+        path: CString,
+        flags: libc::c_int,
+        mask: libc::c_uint,
+    ) -> io::Result<Op<Statx>> {
+        let mut statx = Statx {
+            path,
+            statx: Box::new(unsafe { mem::zeroed() }),
+        };
+        let entry = opcode::Statx::new(
+            types::Fd(dirfd),
+            statx.path.as_ptr(),
+            statx.statx.as_mut() as *mut libc::statx as *mut types::statx,
+        )
+        .flags(flags)
+        .mask(mask)
+        .build();
+        Op::submit(statx, entry)
+    }
+}
+
+impl Completable for Statx {
+    type Output = io::Result<libc::statx>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(*self.statx)
+    }
+}
@@ -0,0 +1,33 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+
+pub(crate) struct SyncFileRange;
+
+impl Op<SyncFileRange> {
+    pub(crate) fn sync_file_range(
+        fd: Fd,
+        offset: u64,
+        len: u32,
+        flags: u32,
+    ) -> io::Result<Op<SyncFileRange>> {
+        let entry = fd.build(|fd| {
+            opcode::SyncFileRange::new(fd, len)
+                .offset(offset)
+                .flags(flags)
+                .build()
+        });
+        Op::submit(SyncFileRange, entry)
+    }
+}
+
+impl Completable for SyncFileRange {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use std::io;
+use std::os::unix::io::RawFd;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Tee;
+
+impl Op<Tee> {
+    pub(crate) fn tee(fd_in: RawFd, fd_out: RawFd, len: u32) -> io::Result<Op<Tee>> {
+        let entry = opcode::Tee::new(types::Fd(fd_in), types::Fd(fd_out), len).build();
+        Op::submit(Tee, entry)
+    }
+}
+
+impl Completable for Tee {
+    type Output = io::Result<usize>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        let n = cqe.result? as usize;
+        Ok(n)
+    }
+}
@@ -0,0 +1,31 @@
+use std::ffi::CString;
+use std::io;
+
+use io_uring::{opcode, types};
+
+use crate::driver::{Completable, CqeResult, Op};
+
+pub(crate) struct Unlink {
+    path: CString,
+}
+
+impl Op<Unlink> {
+    // Remove the file at `path`, or the empty directory if `dir` is set.
+    pub(crate) fn unlink(path: CString, dir: bool) -> io::Result<Op<Unlink>> {
+        let unlink = Unlink { path };
+        let flags = if dir { libc::AT_REMOVEDIR } else { 0 };
+        let entry = opcode::UnlinkAt::new(types::Fd(libc::AT_FDCWD), unlink.path.as_ptr())
+            .flags(flags)
+            .build();
+        Op::submit(unlink, entry)
+    }
+}
+
+impl Completable for Unlink {
+    type Output = io::Result<()>;
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        cqe.result?;
+        Ok(())
+    }
+}
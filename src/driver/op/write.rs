@@ -1,29 +1,28 @@
 use std::io;
-use std::os::unix::io::RawFd;
 
-use io_uring::{opcode, types};
+use io_uring::opcode;
 
-use crate::driver::{Completable, CqeResult, Op};
+use crate::driver::{Completable, CqeResult, Fd, Op};
 
 pub(crate) struct Write {
     buf: Vec<u8>,
 }
 
 impl Op<Write> {
-    pub(crate) fn write(fd: RawFd, buf: &[u8]) -> io::Result<Op<Write>> {
-        let buf = buf.to_vec();
+    pub(crate) fn write(fd: Fd, buf: Vec<u8>) -> io::Result<Op<Write>> {
         let write = Write { buf };
-        let entry =
-            opcode::Write::new(types::Fd(fd), write.buf.as_ptr(), write.buf.len() as u32).build();
+        let entry = fd
+            .build(|fd| opcode::Write::new(fd, write.buf.as_ptr(), write.buf.len() as u32).build());
         Op::submit(write, entry)
     }
 }
 
 impl Completable for Write {
-    type Output = io::Result<usize>;
+    type Output = (io::Result<usize>, Vec<u8>);
 
+    // The buffer is handed back, so that it can be reused.
     fn complete(self, cqe: CqeResult) -> Self::Output {
-        let n = cqe.result? as usize;
-        Ok(n)
+        let res = cqe.result.map(|n| n as usize);
+        (res, self.buf)
     }
 }
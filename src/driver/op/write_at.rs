@@ -0,0 +1,35 @@
+use std::io;
+
+use io_uring::opcode;
+
+use crate::driver::{Completable, CqeResult, Fd, Op};
+use crate::fs::AlignedBuf;
+
+pub(crate) struct WriteAt {
+    buf: AlignedBuf,
+}
+
+impl Op<WriteAt> {
+    // The buffer is handed back whatever the outcome.
+    pub(crate) fn write_at(
+        fd: Fd,
+        buf: AlignedBuf,
+        offset: u64,
+    ) -> Result<Op<WriteAt>, (io::Error, AlignedBuf)> {
+        let entry = fd.build(|fd| {
+            opcode::Write::new(fd, buf.as_ptr(), buf.len() as u32)
+                .offset(offset)
+                .build()
+        });
+        Op::try_submit(WriteAt { buf }, entry).map_err(|(e, write)| (e, write.buf))
+    }
+}
+
+impl Completable for WriteAt {
+    type Output = (io::Result<usize>, AlignedBuf);
+
+    fn complete(self, cqe: CqeResult) -> Self::Output {
+        let res = cqe.result.map(|n| n as usize);
+        (res, self.buf)
+    }
+}
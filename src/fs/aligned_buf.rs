@@ -0,0 +1,161 @@
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// A fixed capacity byte buffer whose memory starts at a multiple of a given alignment, as
+/// `O_DIRECT` I/O requires.
+///
+/// Like `Vec<u8>`, only the first `len` bytes are initialized and accessible.
+pub struct AlignedBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    layout: Layout,
+}
+
+impl AlignedBuf {
+    /// Allocate an empty buffer able to hold `capacity` bytes, aligned to `align`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two, or `capacity` rounded up to `align` overflows.
+    pub fn with_capacity(capacity: usize, align: usize) -> AlignedBuf {
+        let layout = Layout::from_size_align(capacity, align).expect("invalid layout");
+        let ptr = if capacity == 0 {
+            // A dangling pointer respecting the alignment, zero sized allocations are not allowed.
+            NonNull::new(align as *mut u8).unwrap()
+        } else {
+            let ptr = unsafe { alloc::alloc(layout) };
+            NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+        };
+        AlignedBuf {
+            ptr,
+            len: 0,
+            layout,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.layout.size()
+    }
+
+    pub fn align(&self) -> usize {
+        self.layout.align()
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Shortens the buffer to `len` bytes. Has no effect if `len` is greater than the current
+    /// length.
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    /// Append `other` to the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer has not enough capacity left.
+    pub fn extend_from_slice(&mut self, other: &[u8]) {
+        assert!(
+            other.len() <= self.capacity() - self.len,
+            "extend_from_slice out of capacity: {:?} <= {:?}",
+            other.len(),
+            self.capacity() - self.len,
+        );
+        unsafe {
+            self.ptr
+                .as_ptr()
+                .add(self.len)
+                .copy_from_nonoverlapping(other.as_ptr(), other.len());
+        }
+        self.len += other.len();
+    }
+
+    /// Resize the buffer to `len` bytes, filling new bytes with `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is greater than the capacity.
+    pub fn resize(&mut self, len: usize, value: u8) {
+        assert!(
+            len <= self.capacity(),
+            "resize out of capacity: {:?} <= {:?}",
+            len,
+            self.capacity(),
+        );
+        if len > self.len {
+            unsafe {
+                self.ptr
+                    .as_ptr()
+                    .add(self.len)
+                    .write_bytes(value, len - self.len);
+            }
+        }
+        self.len = len;
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+
+    // Set the length after the kernel filled in the first `len` bytes.
+    pub(crate) unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity());
+        self.len = len;
+    }
+}
+
+impl Deref for AlignedBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl AsRef<[u8]> for AlignedBuf {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for AlignedBuf {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl fmt::Debug for AlignedBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlignedBuf")
+            .field("len", &self.len)
+            .field("cap", &self.capacity())
+            .field("align", &self.align())
+            .finish()
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+}
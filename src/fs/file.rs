@@ -0,0 +1,477 @@
+use std::cell::RefCell;
+use std::fs;
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::path::Path;
+use std::pin::Pin;
+use std::ptr;
+use std::task::{ready, Context, Poll};
+
+use futures_io::{AsyncRead, AsyncSeek, AsyncWrite};
+
+use super::{Advice, AlignedBuf, AllocateMode, Metadata, OpenOptions, SyncRangeMode, STATX_MASK};
+use crate::driver::{self, Fd, Op};
+
+/// A file on the filesystem, with all I/O performed through io_uring.
+///
+/// `read_at` and `write_at` access the file at an explicit position, while the `AsyncRead`,
+/// `AsyncWrite` and `AsyncSeek` implementations use a cursor kept by the `File` itself.
+pub struct File {
+    inner: RefCell<Inner>,
+    fd: RawFd,
+    // The slot of the file in the registered file table, used by operations instead of `fd`
+    // once set.
+    fixed: Option<u32>,
+    pos: u64,
+    pub(super) direct: Option<DirectIo>,
+}
+
+// The alignment `O_DIRECT` I/O requires of the memory, and of the file offsets and lengths.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct DirectIo {
+    mem_align: usize,
+    offset_align: usize,
+}
+
+impl DirectIo {
+    // Used when the kernel can't report the alignment (before 6.1), the logical block size of
+    // nearly every device divides it.
+    const FALLBACK: DirectIo = DirectIo {
+        mem_align: 4096,
+        offset_align: 4096,
+    };
+
+    pub(crate) fn from_statx(statx: &libc::statx) -> DirectIo {
+        if statx.stx_mask & libc::STATX_DIOALIGN == 0
+            || statx.stx_dio_mem_align == 0
+            || statx.stx_dio_offset_align == 0
+        {
+            return DirectIo::FALLBACK;
+        }
+        DirectIo {
+            mem_align: statx.stx_dio_mem_align as usize,
+            offset_align: statx.stx_dio_offset_align as usize,
+        }
+    }
+
+    fn check(&self, ptr: *const u8, len: usize, pos: u64) -> io::Result<()> {
+        if !(ptr as usize).is_multiple_of(self.mem_align)
+            || !len.is_multiple_of(self.offset_align)
+            || !pos.is_multiple_of(self.offset_align as u64)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "O_DIRECT I/O requires memory aligned to {} bytes, and offset and length aligned to {} bytes",
+                    self.mem_align, self.offset_align
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl File {
+    /// Open a file in read-only mode.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        OpenOptions::new().read(true).open(path).await
+    }
+
+    /// Open a file in write-only mode, creating it if it does not exist and truncating it if
+    /// it does.
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<File> {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .await
+    }
+
+    pub fn options() -> OpenOptions {
+        OpenOptions::new()
+    }
+
+    pub(crate) fn from_fd(fd: RawFd, direct: Option<DirectIo>) -> File {
+        File {
+            inner: RefCell::new(Inner {
+                read: ReadState::Idle,
+                write: WriteState::Idle,
+                statx: StatxState::Idle,
+            }),
+            fd,
+            fixed: None,
+            pos: 0,
+            direct,
+        }
+    }
+
+    // Used for files opened outside of OpenOptions, where O_DIRECT has to be looked up.
+    fn from_raw(fd: RawFd) -> File {
+        let direct = match syscall!(fcntl(fd, libc::F_GETFL)) {
+            Ok(flags) if flags & libc::O_DIRECT != 0 => Some(DirectIo::FALLBACK),
+            _ => None,
+        };
+        File::from_fd(fd, direct)
+    }
+
+    pub fn from_std(file: fs::File) -> File {
+        File::from_raw(file.into_raw_fd())
+    }
+
+    /// Allocate an empty buffer of `capacity` bytes, aligned as `O_DIRECT` I/O on this file
+    /// requires. For files not opened with `O_DIRECT` no alignment is needed.
+    pub fn aligned_buf(&self, capacity: usize) -> AlignedBuf {
+        let align = self.direct.map_or(1, |direct| direct.mem_align);
+        AlignedBuf::with_capacity(capacity, align)
+    }
+
+    /// Read from the file at `pos` into `buf` without copying, replacing its contents with up
+    /// to its capacity bytes, and returning the number of bytes read along with the buffer.
+    /// The buffer is handed back on errors too, empty. The cursor is not affected.
+    ///
+    /// With `O_DIRECT`, the memory of `buf` must be aligned, and `pos` and the capacity of `buf`
+    /// must be multiples of the block size, otherwise `InvalidInput` is returned.
+    pub async fn read_at_aligned(
+        &self,
+        buf: AlignedBuf,
+        pos: u64,
+    ) -> (io::Result<usize>, AlignedBuf) {
+        if let Some(direct) = self.direct {
+            if let Err(e) = direct.check(buf.as_ptr(), buf.capacity(), pos) {
+                return (Err(e), buf);
+            }
+        }
+        match Op::read_at(self.target(), buf, pos) {
+            Ok(op) => op.await,
+            Err((e, buf)) => (Err(e), buf),
+        }
+    }
+
+    /// Write `buf` to the file at `pos` without copying, returning the number of bytes written
+    /// along with the buffer, which is handed back on errors too. The cursor is not affected.
+    ///
+    /// With `O_DIRECT`, the memory of `buf` must be aligned, and `pos` and the length of `buf`
+    /// must be multiples of the block size, otherwise `InvalidInput` is returned.
+    pub async fn write_at_aligned(
+        &self,
+        buf: AlignedBuf,
+        pos: u64,
+    ) -> (io::Result<usize>, AlignedBuf) {
+        if let Some(direct) = self.direct {
+            if let Err(e) = direct.check(buf.as_ptr(), buf.len(), pos) {
+                return (Err(e), buf);
+            }
+        }
+        match Op::write_at(self.target(), buf, pos) {
+            Ok(op) => op.await,
+            Err((e, buf)) => (Err(e), buf),
+        }
+    }
+
+    /// Read from the file at `pos`, returning the number of bytes read. The cursor is not
+    /// affected.
+    pub async fn read_at(&self, buf: &mut [u8], pos: u64) -> io::Result<usize> {
+        let buf1 = staging_buf(self.direct, buf.len(), pos)?;
+        let (res, buf1) = Op::read_at(self.target(), buf1, pos)
+            .map_err(|(e, _)| e)?
+            .await;
+        let n = res?;
+        buf[..n].copy_from_slice(&buf1);
+        Ok(n)
+    }
+
+    /// Write to the file at `pos`, returning the number of bytes written. The cursor is not
+    /// affected.
+    pub async fn write_at(&self, buf: &[u8], pos: u64) -> io::Result<usize> {
+        let mut buf1 = staging_buf(self.direct, buf.len(), pos)?;
+        buf1.extend_from_slice(buf);
+        Op::write_at(self.target(), buf1, pos)
+            .map_err(|(e, _)| e)?
+            .await
+            .0
+    }
+
+    /// Flush all data and metadata to the device.
+    pub async fn sync_all(&self) -> io::Result<()> {
+        Op::fsync(self.target(), false)?.await
+    }
+
+    /// Flush the data, and the metadata needed to read it back, to the device.
+    pub async fn sync_data(&self) -> io::Result<()> {
+        Op::fsync(self.target(), true)?.await
+    }
+
+    /// Query the metadata of the file.
+    pub async fn metadata(&self) -> io::Result<Metadata> {
+        let statx = Op::statx(self.fd, STATX_MASK)?.await?;
+        Ok(Metadata::new(statx))
+    }
+
+    /// Truncate or extend the file to `size` bytes. The cursor is not affected.
+    pub async fn set_len(&self, size: u64) -> io::Result<()> {
+        Op::ftruncate(self.target(), size)?.await
+    }
+
+    /// Change the space allocated to `len` bytes of the file starting at `offset`, as
+    /// selected by `mode`. The cursor is not affected.
+    pub async fn allocate(&self, offset: u64, len: u64, mode: AllocateMode) -> io::Result<()> {
+        Op::fallocate(self.target(), offset, len, mode.as_raw())?.await
+    }
+
+    /// Announce the access pattern for `len` bytes of the file starting at `offset`, a `len`
+    /// of 0 extends the range to the end of the file. This is only a hint to the page cache.
+    pub async fn advise(&self, offset: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let len = libc::off_t::try_from(len).map_err(|_| invalid_len())?;
+        Op::fadvise(self.target(), offset, len, advice.as_fadvise())?.await
+    }
+
+    /// Write back, or wait for the writeback of, the dirty pages in `len` bytes of the file
+    /// starting at `offset`, as selected by `mode`. A `len` of 0 extends the range to the end
+    /// of the file.
+    ///
+    /// Ranges are limited to `u32::MAX` bytes, larger ones return `InvalidInput`.
+    pub async fn sync_range(&self, offset: u64, len: u64, mode: SyncRangeMode) -> io::Result<()> {
+        let len = u32::try_from(len).map_err(|_| invalid_len())?;
+        Op::sync_file_range(self.target(), offset, len, mode.as_raw())?.await
+    }
+
+    /// Install the file in the registered file table of the runtime, so that later operations
+    /// skip the per-operation file descriptor lookup in the kernel. The file descriptor stays
+    /// open for operations that need it.
+    pub fn register_fixed(&mut self) -> io::Result<()> {
+        if self.fixed.is_none() {
+            self.fixed = Some(driver::register_file(self.fd)?);
+        }
+        Ok(())
+    }
+
+    // The target of io_uring operations on the file.
+    pub(crate) fn target(&self) -> Fd {
+        match self.fixed {
+            Some(index) => Fd::Fixed(index),
+            None => Fd::Raw(self.fd),
+        }
+    }
+
+    /// Close the file, reporting errors that dropping it would ignore.
+    pub async fn close(mut self) -> io::Result<()> {
+        // If the close cannot be submitted, dropping the file closes the descriptor.
+        let op = Op::close(self.fd)?;
+        self.fd = -1;
+        drop(self);
+        op.await
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(this.inner.borrow_mut().poll_read(
+            cx,
+            this.target(),
+            buf,
+            this.pos,
+            this.direct
+        ))?;
+        this.pos += n as u64;
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(this.inner.borrow_mut().poll_write(
+            cx,
+            this.target(),
+            buf,
+            this.pos,
+            this.direct
+        ))?;
+        this.pos += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for File {
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => {
+                this.pos = n;
+                return Poll::Ready(Ok(n));
+            }
+            SeekFrom::Current(n) => (this.pos, n),
+            SeekFrom::End(n) => {
+                let statx = ready!(this.inner.borrow_mut().poll_statx(cx, this.fd))?;
+                (statx.stx_size, n)
+            }
+        };
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                this.pos = n;
+                Poll::Ready(Ok(n))
+            }
+            None => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ))),
+        }
+    }
+}
+
+// A buffer for the kernel to read into or write from, aligned as `O_DIRECT` requires, only the
+// offset and length then come from the caller.
+fn staging_buf(direct: Option<DirectIo>, len: usize, pos: u64) -> io::Result<AlignedBuf> {
+    match direct {
+        Some(direct) => {
+            direct.check(ptr::null(), len, pos)?;
+            Ok(AlignedBuf::with_capacity(len, direct.mem_align))
+        }
+        None => Ok(AlignedBuf::with_capacity(len, 1)),
+    }
+}
+
+fn invalid_len() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "range length too large")
+}
+
+impl AsRawFd for File {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd
+    }
+}
+
+impl FromRawFd for File {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        File::from_raw(fd)
+    }
+}
+
+impl Drop for File {
+    fn drop(&mut self) {
+        if let Some(index) = self.fixed {
+            driver::unregister_file(index);
+        }
+        if self.fd >= 0 {
+            let _ = unsafe { libc::close(self.fd) };
+        }
+    }
+}
+
+// The ops of the `AsyncRead`, `AsyncWrite` and `AsyncSeek` implementations, which resume them
+// when polled again. Positional I/O starts an op per call instead.
+struct Inner {
+    read: ReadState,
+    write: WriteState,
+    statx: StatxState,
+}
+
+enum ReadState {
+    Idle,
+    // Reading at the position.
+    Reading(Op<driver::ReadAt>, u64),
+}
+
+enum WriteState {
+    Idle,
+    Writing(Op<driver::WriteAt>),
+}
+
+enum StatxState {
+    Idle,
+    Statxing(Op<driver::Statx>),
+}
+
+impl Inner {
+    fn poll_read(
+        &mut self,
+        cx: &mut Context,
+        fd: Fd,
+        buf: &mut [u8],
+        pos: u64,
+        direct: Option<DirectIo>,
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match &mut self.read {
+                ReadState::Idle => {
+                    let buf1 = staging_buf(direct, buf.len(), pos)?;
+                    let op = Op::read_at(fd, buf1, pos).map_err(|(e, _)| e)?;
+                    self.read = ReadState::Reading(op, pos);
+                }
+                ReadState::Reading(op, op_pos) => {
+                    let op_pos = *op_pos;
+                    let (res, buf1) = ready!(Pin::new(op).poll(cx));
+                    self.read = ReadState::Idle;
+                    // The cursor moved since the read started, its data is not wanted.
+                    if op_pos != pos {
+                        continue;
+                    }
+                    // A read started for a larger buffer, the rest is read again at the next
+                    // position.
+                    let n = res?.min(buf.len());
+                    buf[..n].copy_from_slice(&buf1[..n]);
+                    return Poll::Ready(Ok(n));
+                }
+            }
+        }
+    }
+
+    fn poll_write(
+        &mut self,
+        cx: &mut Context,
+        fd: Fd,
+        buf: &[u8],
+        pos: u64,
+        direct: Option<DirectIo>,
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match &mut self.write {
+                WriteState::Idle => {
+                    let mut buf1 = staging_buf(direct, buf.len(), pos)?;
+                    buf1.extend_from_slice(buf);
+                    let op = Op::write_at(fd, buf1, pos).map_err(|(e, _)| e)?;
+                    self.write = WriteState::Writing(op);
+                }
+                WriteState::Writing(op) => {
+                    let res = ready!(Pin::new(op).poll(cx));
+                    self.write = WriteState::Idle;
+                    return Poll::Ready(res.0);
+                }
+            }
+        }
+    }
+
+    fn poll_statx(&mut self, cx: &mut Context, fd: RawFd) -> Poll<io::Result<libc::statx>> {
+        loop {
+            match &mut self.statx {
+                StatxState::Idle => {
+                    self.statx = StatxState::Statxing(Op::statx(fd, STATX_MASK)?);
+                }
+                StatxState::Statxing(op) => {
+                    let res = ready!(Pin::new(op).poll(cx));
+                    self.statx = StatxState::Idle;
+                    return Poll::Ready(res);
+                }
+            }
+        }
+    }
+}
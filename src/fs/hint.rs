@@ -0,0 +1,110 @@
+/// How [`File::allocate`] changes the space allocated to a range of a file.
+///
+/// [`File::allocate`]: super::File::allocate
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AllocateMode {
+    /// Allocate the range, extending the file size if it ends past the end of the file.
+    Extend,
+    /// Allocate the range without changing the file size, space past the end of the file is
+    /// preallocated for later writes.
+    KeepSize,
+    /// Deallocate the range, leaving a hole that reads back as zeros. The file size is not
+    /// changed.
+    PunchHole,
+    /// Zero the range, allocating it and extending the file size if needed. Unlike writing
+    /// zeros, no data is transferred to the device on most filesystems.
+    ZeroRange,
+    /// Like `ZeroRange`, without changing the file size.
+    ZeroRangeKeepSize,
+}
+
+impl AllocateMode {
+    pub(crate) fn as_raw(self) -> i32 {
+        match self {
+            AllocateMode::Extend => 0,
+            AllocateMode::KeepSize => libc::FALLOC_FL_KEEP_SIZE,
+            AllocateMode::PunchHole => libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+            AllocateMode::ZeroRange => libc::FALLOC_FL_ZERO_RANGE,
+            AllocateMode::ZeroRangeKeepSize => {
+                libc::FALLOC_FL_ZERO_RANGE | libc::FALLOC_FL_KEEP_SIZE
+            }
+        }
+    }
+}
+
+/// The expected access pattern of a range of a file or of memory, passed to [`File::advise`]
+/// and [`madvise`].
+///
+/// [`File::advise`]: super::File::advise
+/// [`madvise`]: super::madvise
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Advice {
+    /// No particular pattern, the default.
+    Normal,
+    /// The range is accessed sequentially, read-ahead is made more aggressive.
+    Sequential,
+    /// The range is accessed in random order, read-ahead is disabled.
+    Random,
+    /// The range will be accessed soon, reading it in starts in the background.
+    WillNeed,
+    /// The range will not be accessed soon, its cached pages can be dropped.
+    DontNeed,
+    /// The range will be accessed only once. Only supported for files.
+    NoReuse,
+}
+
+impl Advice {
+    pub(crate) fn as_fadvise(self) -> i32 {
+        match self {
+            Advice::Normal => libc::POSIX_FADV_NORMAL,
+            Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+            Advice::Random => libc::POSIX_FADV_RANDOM,
+            Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+            Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+            Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+        }
+    }
+
+    pub(crate) fn as_madvise(self) -> Option<i32> {
+        match self {
+            Advice::Normal => Some(libc::MADV_NORMAL),
+            Advice::Sequential => Some(libc::MADV_SEQUENTIAL),
+            Advice::Random => Some(libc::MADV_RANDOM),
+            Advice::WillNeed => Some(libc::MADV_WILLNEED),
+            Advice::DontNeed => Some(libc::MADV_DONTNEED),
+            Advice::NoReuse => None,
+        }
+    }
+}
+
+/// What [`File::sync_range`] waits for and starts.
+///
+/// None of the modes flush the file metadata or the device write cache, so they give no
+/// durability guarantee on their own. They are useful to start writeback early and bound the
+/// work a later `sync_data` has to do.
+///
+/// [`File::sync_range`]: super::File::sync_range
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncRangeMode {
+    /// Start writeback of the dirty pages in the range, without waiting for it.
+    Start,
+    /// Wait for writeback of the range already in progress, without starting more.
+    Wait,
+    /// Wait for writeback in progress, start writeback of the dirty pages, and wait for it to
+    /// complete.
+    StartAndWait,
+}
+
+impl SyncRangeMode {
+    pub(crate) fn as_raw(self) -> u32 {
+        match self {
+            SyncRangeMode::Start => libc::SYNC_FILE_RANGE_WRITE,
+            SyncRangeMode::Wait => libc::SYNC_FILE_RANGE_WAIT_BEFORE,
+            SyncRangeMode::StartAndWait => {
+                libc::SYNC_FILE_RANGE_WAIT_BEFORE
+                    | libc::SYNC_FILE_RANGE_WRITE
+                    | libc::SYNC_FILE_RANGE_WAIT_AFTER
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::io;
+use std::time::{Duration, SystemTime};
+
+/// Metadata about a file, as returned by `statx(2)`.
+#[derive(Clone)]
+pub struct Metadata {
+    statx: libc::statx,
+}
+
+impl Metadata {
+    pub(crate) fn new(statx: libc::statx) -> Metadata {
+        Metadata { statx }
+    }
+
+    /// The size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.statx.stx_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type() == libc::S_IFDIR
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.file_type() == libc::S_IFREG
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.file_type() == libc::S_IFLNK
+    }
+
+    /// The file type and permission bits, as `st_mode` of `stat(2)`.
+    pub fn mode(&self) -> u32 {
+        self.statx.stx_mode as u32
+    }
+
+    pub fn ino(&self) -> u64 {
+        self.statx.stx_ino
+    }
+
+    /// The id of the device containing the file.
+    pub fn dev(&self) -> u64 {
+        libc::makedev(self.statx.stx_dev_major, self.statx.stx_dev_minor)
+    }
+
+    pub fn nlink(&self) -> u64 {
+        self.statx.stx_nlink as u64
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.statx.stx_uid
+    }
+
+    pub fn gid(&self) -> u32 {
+        self.statx.stx_gid
+    }
+
+    /// The preferred block size for I/O.
+    pub fn blksize(&self) -> u64 {
+        self.statx.stx_blksize as u64
+    }
+
+    /// The number of 512 byte blocks allocated to the file.
+    pub fn blocks(&self) -> u64 {
+        self.statx.stx_blocks
+    }
+
+    pub fn accessed(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_ATIME, self.statx.stx_atime)
+    }
+
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_MTIME, self.statx.stx_mtime)
+    }
+
+    /// The creation time, not every filesystem records it.
+    pub fn created(&self) -> io::Result<SystemTime> {
+        self.time(libc::STATX_BTIME, self.statx.stx_btime)
+    }
+
+    fn file_type(&self) -> libc::mode_t {
+        self.statx.stx_mode as libc::mode_t & libc::S_IFMT
+    }
+
+    fn time(&self, mask: libc::c_uint, ts: libc::statx_timestamp) -> io::Result<SystemTime> {
+        if self.statx.stx_mask & mask == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "timestamp not available on this filesystem",
+            ));
+        }
+        let since_epoch = Duration::new(ts.tv_sec.unsigned_abs(), ts.tv_nsec);
+        let time = if ts.tv_sec >= 0 {
+            SystemTime::UNIX_EPOCH.checked_add(since_epoch)
+        } else {
+            SystemTime::UNIX_EPOCH.checked_sub(since_epoch)
+        };
+        time.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "timestamp out of range"))
+    }
+}
+
+impl fmt::Debug for Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metadata")
+            .field("len", &self.len())
+            .field("mode", &format_args!("{:#o}", self.mode()))
+            .field("ino", &self.ino())
+            .field("nlink", &self.nlink())
+            .field("uid", &self.uid())
+            .field("gid", &self.gid())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,138 @@
+mod aligned_buf;
+mod file;
+mod hint;
+mod metadata;
+mod open_options;
+
+pub use aligned_buf::AlignedBuf;
+pub use file::File;
+pub use hint::{Advice, AllocateMode, SyncRangeMode};
+pub use metadata::Metadata;
+pub use open_options::OpenOptions;
+
+use std::io;
+use std::path::Path;
+
+use crate::driver::Op;
+use open_options::cstr;
+
+// The statx fields filled in for `Metadata`.
+const STATX_MASK: libc::c_uint = libc::STATX_BASIC_STATS | libc::STATX_BTIME;
+
+// The size of each read done by `read` past the length reported by the file metadata.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Query the metadata of the file at `path`, following symbolic links.
+pub async fn metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+    let path = cstr(path.as_ref())?;
+    let statx = Op::statx_at(libc::AT_FDCWD, path, 0, STATX_MASK)?.await?;
+    Ok(Metadata::new(statx))
+}
+
+/// Query the metadata of the file at `path`, without following symbolic links.
+pub async fn symlink_metadata<P: AsRef<Path>>(path: P) -> io::Result<Metadata> {
+    let path = cstr(path.as_ref())?;
+    let statx = Op::statx_at(libc::AT_FDCWD, path, libc::AT_SYMLINK_NOFOLLOW, STATX_MASK)?.await?;
+    Ok(Metadata::new(statx))
+}
+
+/// Create a new, empty directory at `path`.
+pub async fn create_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    Op::mkdir(cstr(path.as_ref())?, 0o777)?.await
+}
+
+/// Remove the file at `path`, which must not be a directory.
+pub async fn remove_file<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    Op::unlink(cstr(path.as_ref())?, false)?.await
+}
+
+/// Remove the empty directory at `path`.
+pub async fn remove_dir<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    Op::unlink(cstr(path.as_ref())?, true)?.await
+}
+
+/// Rename `from` to `to`, replacing `to` if it exists.
+pub async fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> io::Result<()> {
+    Op::rename(cstr(from.as_ref())?, cstr(to.as_ref())?)?.await
+}
+
+/// Create a new hard link `link` to the file `original`.
+pub async fn hard_link<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
+    Op::hard_link(cstr(original.as_ref())?, cstr(link.as_ref())?)?.await
+}
+
+/// Create a new symbolic link `link` pointing to `original`.
+pub async fn symlink<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> io::Result<()> {
+    Op::symlink(cstr(original.as_ref())?, cstr(link.as_ref())?)?.await
+}
+
+/// Read the entire contents of the file at `path`.
+pub async fn read<P: AsRef<Path>>(path: P) -> io::Result<Vec<u8>> {
+    let file = File::open(path).await?;
+    // The length is only a hint, files in /proc for instance report 0.
+    let len = file.metadata().await.map(|m| m.len() as usize).unwrap_or(0);
+    let mut buf = vec![0; len.max(READ_CHUNK_SIZE)];
+    let mut pos = 0;
+    loop {
+        if pos == buf.len() {
+            buf.resize(pos + READ_CHUNK_SIZE, 0);
+        }
+        let n = file.read_at(&mut buf[pos..], pos as u64).await?;
+        if n == 0 {
+            break;
+        }
+        pos += n;
+    }
+    buf.truncate(pos);
+    file.close().await?;
+    Ok(buf)
+}
+
+/// Read the entire contents of the file at `path` into a string.
+pub async fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let buf = read(path).await?;
+    String::from_utf8(buf).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+    })
+}
+
+/// Write `contents` to the file at `path`, creating it if it does not exist and replacing its
+/// contents if it does.
+pub async fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> io::Result<()> {
+    let file = File::create(path).await?;
+    let mut buf = contents.as_ref();
+    let mut pos = 0;
+    while !buf.is_empty() {
+        let n = file.write_at(buf, pos).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        buf = &buf[n..];
+        pos += n as u64;
+    }
+    file.close().await
+}
+
+/// Announce the access pattern for the `len` bytes of memory starting at `addr`.
+///
+/// `Advice::NoReuse` only applies to files and returns `InvalidInput`.
+///
+/// # Safety
+///
+/// The range must be mapped memory owned by the caller. `Advice::DontNeed` discards the
+/// contents of private mappings, which then read back as zeros or as the file contents, so
+/// no references into the range may be alive.
+pub async unsafe fn madvise(addr: *const u8, len: usize, advice: Advice) -> io::Result<()> {
+    let advice = advice.as_madvise().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "advice not supported for memory",
+        )
+    })?;
+    let len = libc::off_t::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "range length too large"))?;
+    Op::madvise(addr.cast(), len, advice)?.await
+}
@@ -0,0 +1,151 @@
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use super::file::DirectIo;
+use super::File;
+use crate::driver::Op;
+
+#[derive(Clone, Debug)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+    direct: bool,
+    mode: libc::mode_t,
+    custom_flags: libc::c_int,
+}
+
+impl OpenOptions {
+    pub fn new() -> OpenOptions {
+        OpenOptions {
+            read: false,
+            write: false,
+            append: false,
+            truncate: false,
+            create: false,
+            create_new: false,
+            direct: false,
+            mode: 0o666,
+            custom_flags: 0,
+        }
+    }
+
+    pub fn read(&mut self, read: bool) -> &mut OpenOptions {
+        self.read = read;
+        self
+    }
+
+    pub fn write(&mut self, write: bool) -> &mut OpenOptions {
+        self.write = write;
+        self
+    }
+
+    pub fn append(&mut self, append: bool) -> &mut OpenOptions {
+        self.append = append;
+        self
+    }
+
+    pub fn truncate(&mut self, truncate: bool) -> &mut OpenOptions {
+        self.truncate = truncate;
+        self
+    }
+
+    pub fn create(&mut self, create: bool) -> &mut OpenOptions {
+        self.create = create;
+        self
+    }
+
+    pub fn create_new(&mut self, create_new: bool) -> &mut OpenOptions {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Open the file with `O_DIRECT`, bypassing the page cache.
+    ///
+    /// Reads and writes must then use offsets and lengths aligned to the block size of the
+    /// file, and `read_at_aligned` and `write_at_aligned` additionally need memory allocated by
+    /// `File::aligned_buf`. Misaligned I/O fails with `InvalidInput` before reaching the kernel.
+    pub fn direct(&mut self, direct: bool) -> &mut OpenOptions {
+        self.direct = direct;
+        self
+    }
+
+    /// Sets the permissions a new file is created with, before the umask is applied.
+    pub fn mode(&mut self, mode: u32) -> &mut OpenOptions {
+        self.mode = mode as libc::mode_t;
+        self
+    }
+
+    /// Pass extra flags to `open(2)`, the access mode bits are masked out.
+    pub fn custom_flags(&mut self, flags: i32) -> &mut OpenOptions {
+        self.custom_flags = flags;
+        self
+    }
+
+    pub async fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<File> {
+        let path = cstr(path.as_ref())?;
+        let mut flags =
+            self.access_mode()? | self.creation_mode()? | (self.custom_flags & !libc::O_ACCMODE);
+        if self.direct {
+            flags |= libc::O_DIRECT;
+        }
+        let fd = Op::open(path, flags, self.mode)?.await?;
+        let mut file = File::from_fd(fd, None);
+        if flags & libc::O_DIRECT != 0 {
+            let statx = Op::statx(fd, libc::STATX_DIOALIGN)?.await?;
+            file.direct = Some(DirectIo::from_statx(&statx));
+        }
+        Ok(file)
+    }
+
+    fn access_mode(&self) -> io::Result<libc::c_int> {
+        match (self.read, self.write, self.append) {
+            (true, false, false) => Ok(libc::O_RDONLY),
+            (false, true, false) => Ok(libc::O_WRONLY),
+            (true, true, false) => Ok(libc::O_RDWR),
+            (false, _, true) => Ok(libc::O_WRONLY | libc::O_APPEND),
+            (true, _, true) => Ok(libc::O_RDWR | libc::O_APPEND),
+            (false, false, false) => Err(io::Error::from(io::ErrorKind::InvalidInput)),
+        }
+    }
+
+    fn creation_mode(&self) -> io::Result<libc::c_int> {
+        match (self.write, self.append) {
+            (true, false) => {}
+            (false, false) => {
+                if self.truncate || self.create || self.create_new {
+                    return Err(io::Error::from(io::ErrorKind::InvalidInput));
+                }
+            }
+            (_, true) => {
+                if self.truncate && !self.create_new {
+                    return Err(io::Error::from(io::ErrorKind::InvalidInput));
+                }
+            }
+        }
+
+        Ok(match (self.create, self.truncate, self.create_new) {
+            (false, false, false) => 0,
+            (true, false, false) => libc::O_CREAT,
+            (false, true, false) => libc::O_TRUNC,
+            (true, true, false) => libc::O_CREAT | libc::O_TRUNC,
+            (_, _, true) => libc::O_CREAT | libc::O_EXCL,
+        })
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> OpenOptions {
+        OpenOptions::new()
+    }
+}
+
+pub(crate) fn cstr(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains a nul byte"))
+}
@@ -0,0 +1,130 @@
+mod pipe;
+
+use std::future::{poll_fn, Future};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::pin::{pin, Pin};
+use std::task::Poll;
+
+use futures_io::AsyncWrite;
+
+use crate::driver::{Fd, Op};
+use crate::fs::File;
+use crate::net::TcpStream;
+use pipe::{Pipe, PIPE_SIZE};
+
+/// Move up to `len` bytes from `from` to `to` without copying them through userspace,
+/// returning the number of bytes moved, or 0 once `from` reached end of file.
+///
+/// The bytes pass through a pipe kept per thread and reused across calls, so a single call
+/// moves at most the pipe capacity of 64 KiB. Both descriptors are used at their current position. If writing
+/// to `to` fails, the bytes already taken from `from` are lost.
+pub async fn splice<R: AsRawFd, W: AsRawFd>(from: &R, to: &W, len: usize) -> io::Result<usize> {
+    let mut pipe = Pipe::take()?;
+    let n = pipe.fill(Fd::Raw(from.as_raw_fd()), len).await?;
+    pipe.drain(Fd::Raw(to.as_raw_fd())).await?;
+    pipe.recycle();
+    Ok(n)
+}
+
+/// Duplicate up to `len` bytes from the pipe `from` to the pipe `to` without consuming them
+/// from `from`, returning the number of bytes duplicated.
+///
+/// Both descriptors must be pipes, otherwise `EINVAL` is returned.
+pub async fn tee<R: AsRawFd, W: AsRawFd>(from: &R, to: &W, len: usize) -> io::Result<usize> {
+    let len = len.min(u32::MAX as usize) as u32;
+    Op::tee(from.as_raw_fd(), to.as_raw_fd(), len)?.await
+}
+
+/// Copy data in both directions between `a` and `b` until both reached end of file, without
+/// copying it through userspace. Returns the number of bytes copied from `a` to `b`, and from
+/// `b` to `a`.
+///
+/// Once one side reached end of file, the write half of the other side is shut down. Bytes
+/// already buffered by `poll_read` or `poll_fill_buf` are written out first, after flushing
+/// buffered writes of both streams.
+pub async fn copy_bidirectional_splice(
+    a: &mut TcpStream,
+    b: &mut TcpStream,
+) -> io::Result<(u64, u64)> {
+    poll_fn(|cx| Pin::new(&mut *a).poll_flush(cx)).await?;
+    poll_fn(|cx| Pin::new(&mut *b).poll_flush(cx)).await?;
+    let a_to_b = copy_splice(a.target(), b.target(), a.take_buffered());
+    let b_to_a = copy_splice(b.target(), a.target(), b.take_buffered());
+    let mut a_to_b = pin!(a_to_b);
+    let mut b_to_a = pin!(b_to_a);
+    let mut a_to_b_done = None;
+    let mut b_to_a_done = None;
+
+    poll_fn(|cx| {
+        if a_to_b_done.is_none() {
+            if let Poll::Ready(n) = a_to_b.as_mut().poll(cx) {
+                a_to_b_done = Some(n?);
+            }
+        }
+        if b_to_a_done.is_none() {
+            if let Poll::Ready(n) = b_to_a.as_mut().poll(cx) {
+                b_to_a_done = Some(n?);
+            }
+        }
+        match (a_to_b_done, b_to_a_done) {
+            (Some(a_to_b), Some(b_to_a)) => Poll::Ready(Ok((a_to_b, b_to_a))),
+            _ => Poll::Pending,
+        }
+    })
+    .await
+}
+
+async fn copy_splice(from: Fd, to: Fd, buffered: Vec<u8>) -> io::Result<u64> {
+    let mut total = 0;
+    let mut buf = &buffered[..];
+    while !buf.is_empty() {
+        let n = Op::write(to, buf.to_vec())?.await.0?;
+        if n == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        buf = &buf[n..];
+        total += n as u64;
+    }
+
+    let mut pipe = Pipe::new()?;
+    loop {
+        let n = pipe.fill(from, PIPE_SIZE).await?;
+        if n == 0 {
+            break;
+        }
+        pipe.drain(to).await?;
+        total += n as u64;
+    }
+    Op::shutdown(to, libc::SHUT_WR)?.await?;
+    Ok(total)
+}
+
+// Send `len` bytes of `file` starting at `offset` to the socket `fd`, through a pipe, stopping
+// early at end of file. Errors are only returned when no byte was sent, otherwise the number
+// of bytes that reached the socket is.
+pub(crate) async fn send_file(fd: Fd, file: &File, offset: u64, len: u64) -> io::Result<u64> {
+    let mut pipe = Pipe::new()?;
+    let mut filled = 0;
+    let res = async {
+        while filled < len {
+            let pos = i64::try_from(offset + filled)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset too large"))?;
+            let chunk = (len - filled).min(PIPE_SIZE as u64) as usize;
+            let n = pipe.fill_at(file.target(), pos, chunk).await?;
+            if n == 0 {
+                break;
+            }
+            filled += n as u64;
+            pipe.drain(fd).await?;
+        }
+        Ok(())
+    }
+    .await;
+
+    let sent = filled - pipe.len() as u64;
+    match res {
+        Err(e) if sent == 0 => Err(e),
+        _ => Ok(sent),
+    }
+}
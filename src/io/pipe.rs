@@ -0,0 +1,81 @@
+use std::cell::Cell;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+use crate::driver::{Fd, Op};
+
+// The capacity of a pipe unless changed with `F_SETPIPE_SZ`, bounding the bytes moved by a
+// single splice into it.
+pub(crate) const PIPE_SIZE: usize = 64 * 1024;
+
+thread_local! {
+    // An empty pipe kept for the next `splice` of the thread.
+    static IDLE: Cell<Option<Pipe>> = const { Cell::new(None) };
+}
+
+// A pipe used as the kernel side buffer data is spliced through, keeping count of the bytes
+// it holds.
+pub(crate) struct Pipe {
+    read: OwnedFd,
+    write: OwnedFd,
+    len: usize,
+}
+
+impl Pipe {
+    pub(crate) fn new() -> io::Result<Pipe> {
+        let mut fds = [-1; 2];
+        syscall!(pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+        Ok(Pipe {
+            read: unsafe { OwnedFd::from_raw_fd(fds[0]) },
+            write: unsafe { OwnedFd::from_raw_fd(fds[1]) },
+            len: 0,
+        })
+    }
+
+    // Take the idle pipe of the thread, or create one if it is in use.
+    pub(crate) fn take() -> io::Result<Pipe> {
+        match IDLE.take() {
+            Some(pipe) => Ok(pipe),
+            None => Pipe::new(),
+        }
+    }
+
+    // Keep the pipe as the idle pipe of the thread, unless it still holds bytes.
+    pub(crate) fn recycle(self) {
+        if self.len == 0 {
+            IDLE.set(Some(self));
+        }
+    }
+
+    // Move up to `len` bytes from `fd` into the pipe, returning 0 at end of file.
+    pub(crate) async fn fill(&mut self, fd: Fd, len: usize) -> io::Result<usize> {
+        self.fill_at(fd, -1, len).await
+    }
+
+    // Like `fill`, reading `fd` at `offset` instead of its current position, unless `offset`
+    // is -1.
+    pub(crate) async fn fill_at(&mut self, fd: Fd, offset: i64, len: usize) -> io::Result<usize> {
+        let len = len.min(u32::MAX as usize) as u32;
+        let n = Op::splice(fd, offset, Fd::Raw(self.write.as_raw_fd()), -1, len)?.await?;
+        self.len += n;
+        Ok(n)
+    }
+
+    // The number of bytes held by the pipe.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    // Move all bytes held by the pipe to `fd`.
+    pub(crate) async fn drain(&mut self, fd: Fd) -> io::Result<()> {
+        while self.len > 0 {
+            let len = self.len.min(u32::MAX as usize) as u32;
+            let n = Op::splice(Fd::Raw(self.read.as_raw_fd()), -1, fd, -1, len)?.await?;
+            if n == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            self.len -= n;
+        }
+        Ok(())
+    }
+}
@@ -10,8 +10,10 @@ macro_rules! syscall {
     }};
 }
 
-mod buffer;
+pub mod buffer;
 pub(crate) mod driver;
+pub mod fs;
+pub mod io;
 mod local_executor;
 pub mod net;
 pub mod runtime;
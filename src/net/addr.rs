@@ -0,0 +1,126 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+use super::dns;
+
+/// Values that can be resolved to socket addresses, like `std::net::ToSocketAddrs`, but
+/// resolving host names with the asynchronous resolver of [`lookup_host`](super::lookup_host)
+/// instead of blocking the thread in `getaddrinfo`.
+///
+/// This trait is sealed, it is implemented for the same types as its std counterpart.
+pub trait ToSocketAddrs: sealed::ToSocketAddrsPriv {}
+
+pub(crate) use sealed::Target;
+
+mod sealed {
+    use std::io;
+    use std::net::SocketAddr;
+
+    // Either the addresses themselves, or a host name to resolve and a port.
+    pub enum Target<'a> {
+        Addrs(Vec<SocketAddr>),
+        Host(&'a str, u16),
+    }
+
+    pub trait ToSocketAddrsPriv {
+        fn to_target(&self) -> io::Result<Target<'_>>;
+    }
+}
+
+// Resolve `addr`, resolving a host name with the resolver of the thread.
+pub(crate) async fn resolve<A: ToSocketAddrs>(addr: A) -> io::Result<Vec<SocketAddr>> {
+    match addr.to_target()? {
+        Target::Addrs(addrs) => Ok(addrs),
+        Target::Host(host, port) => dns::resolve(host, port).await,
+    }
+}
+
+// The target of `host` and `port`, parsing `host` first as an IP address.
+fn host_target(host: &str, port: u16) -> Target<'_> {
+    match host.parse::<IpAddr>() {
+        Ok(ip) => Target::Addrs(vec![SocketAddr::new(ip, port)]),
+        Err(_) => Target::Host(host, port),
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+macro_rules! impl_addr {
+    ($($ty:ty),*) => {
+        $(
+            impl ToSocketAddrs for $ty {}
+
+            impl sealed::ToSocketAddrsPriv for $ty {
+                fn to_target(&self) -> io::Result<Target<'_>> {
+                    Ok(Target::Addrs(vec![SocketAddr::from(*self)]))
+                }
+            }
+        )*
+    };
+}
+
+impl_addr!(
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6,
+    (IpAddr, u16),
+    (Ipv4Addr, u16),
+    (Ipv6Addr, u16)
+);
+
+impl ToSocketAddrs for [SocketAddr] {}
+
+impl sealed::ToSocketAddrsPriv for [SocketAddr] {
+    fn to_target(&self) -> io::Result<Target<'_>> {
+        Ok(Target::Addrs(self.to_vec()))
+    }
+}
+
+impl ToSocketAddrs for (&str, u16) {}
+
+impl sealed::ToSocketAddrsPriv for (&str, u16) {
+    fn to_target(&self) -> io::Result<Target<'_>> {
+        Ok(host_target(self.0, self.1))
+    }
+}
+
+impl ToSocketAddrs for (String, u16) {}
+
+impl sealed::ToSocketAddrsPriv for (String, u16) {
+    fn to_target(&self) -> io::Result<Target<'_>> {
+        Ok(host_target(&self.0, self.1))
+    }
+}
+
+impl ToSocketAddrs for str {}
+
+impl sealed::ToSocketAddrsPriv for str {
+    fn to_target(&self) -> io::Result<Target<'_>> {
+        if let Ok(addr) = self.parse() {
+            return Ok(Target::Addrs(vec![addr]));
+        }
+        let (host, port) = self
+            .rsplit_once(':')
+            .ok_or_else(|| invalid("invalid socket address"))?;
+        let port = port.parse().map_err(|_| invalid("invalid port value"))?;
+        Ok(host_target(host, port))
+    }
+}
+
+impl ToSocketAddrs for String {}
+
+impl sealed::ToSocketAddrsPriv for String {
+    fn to_target(&self) -> io::Result<Target<'_>> {
+        self.as_str().to_target()
+    }
+}
+
+impl<T: ToSocketAddrs + ?Sized> ToSocketAddrs for &T {}
+
+impl<T: ToSocketAddrs + ?Sized> sealed::ToSocketAddrsPriv for &T {
+    fn to_target(&self) -> io::Result<Target<'_>> {
+        (**self).to_target()
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+
+use crate::fs;
+
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+const HOSTS: &str = "/etc/hosts";
+
+// The limits and defaults of glibc, see resolv.conf(5).
+const MAXNS: usize = 3;
+const MAXDNSRCH: usize = 6;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_TIMEOUT: u64 = 30;
+const DEFAULT_ATTEMPTS: usize = 2;
+const MAX_ATTEMPTS: usize = 5;
+const MAX_NDOTS: usize = 15;
+
+// The settings of `resolv.conf` the resolver uses.
+pub(super) struct Config {
+    pub(super) nameservers: Vec<SocketAddr>,
+    pub(super) search: Vec<String>,
+    pub(super) ndots: usize,
+    pub(super) timeout: Duration,
+    pub(super) attempts: usize,
+}
+
+impl Config {
+    pub(super) fn new(nameservers: Vec<SocketAddr>) -> Config {
+        Config {
+            nameservers,
+            search: Vec::new(),
+            ndots: 1,
+            timeout: DEFAULT_TIMEOUT,
+            attempts: DEFAULT_ATTEMPTS,
+        }
+    }
+
+    // Read `/etc/resolv.conf`, falling back to a name server on the local host like glibc
+    // when it is missing or lists none.
+    pub(super) async fn load() -> Config {
+        let text = fs::read_to_string(RESOLV_CONF).await.unwrap_or_default();
+        let mut config = Config::parse(&text);
+        if config.nameservers.is_empty() {
+            config
+                .nameservers
+                .push(SocketAddr::from((Ipv4Addr::LOCALHOST, 53)));
+        }
+        config
+    }
+
+    fn parse(text: &str) -> Config {
+        let mut config = Config::new(Vec::new());
+        for line in text.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("nameserver") => {
+                    // Link local IPv6 addresses may carry a scope, which is not supported.
+                    let ip = words.next().and_then(|ip| ip.parse::<IpAddr>().ok());
+                    if let Some(ip) = ip {
+                        if config.nameservers.len() < MAXNS {
+                            config.nameservers.push(SocketAddr::new(ip, 53));
+                        }
+                    }
+                }
+                // The last of the `domain` and `search` lines wins.
+                Some("domain") => {
+                    config.search = words.next().map(search_domain).into_iter().collect();
+                }
+                Some("search") => {
+                    config.search = words.take(MAXDNSRCH).map(search_domain).collect();
+                }
+                Some("options") => {
+                    for option in words {
+                        let (name, value) = option.split_once(':').unwrap_or((option, ""));
+                        let value = value.parse::<usize>().ok();
+                        match (name, value) {
+                            ("ndots", Some(n)) => config.ndots = n.min(MAX_NDOTS),
+                            ("timeout", Some(n)) => {
+                                config.timeout = Duration::from_secs((n as u64).min(MAX_TIMEOUT))
+                            }
+                            ("attempts", Some(n)) => config.attempts = n.min(MAX_ATTEMPTS),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        config.attempts = config.attempts.max(1);
+        config
+    }
+}
+
+fn search_domain(domain: &str) -> String {
+    domain.trim_end_matches('.').to_ascii_lowercase()
+}
+
+// The addresses of the names listed in `/etc/hosts`.
+pub(super) struct Hosts {
+    addrs: HashMap<String, Vec<IpAddr>>,
+}
+
+impl Hosts {
+    pub(super) fn empty() -> Hosts {
+        Hosts {
+            addrs: HashMap::new(),
+        }
+    }
+
+    pub(super) async fn load() -> Hosts {
+        Hosts::parse(&fs::read_to_string(HOSTS).await.unwrap_or_default())
+    }
+
+    fn parse(text: &str) -> Hosts {
+        let mut hosts = Hosts::empty();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap();
+            let mut words = line.split_whitespace();
+            let Some(ip) = words.next().and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+                continue;
+            };
+            for name in words {
+                let addrs = hosts.addrs.entry(name.to_ascii_lowercase()).or_default();
+                if !addrs.contains(&ip) {
+                    addrs.push(ip);
+                }
+            }
+        }
+        hosts
+    }
+
+    // The addresses of `name`, a lower case name without trailing dot. `localhost` resolves
+    // to the loopback addresses unless listed.
+    pub(super) fn get(&self, name: &str) -> Option<Vec<IpAddr>> {
+        match self.addrs.get(name) {
+            Some(addrs) => Some(addrs.clone()),
+            None if name == "localhost" => Some(vec![
+                IpAddr::V6(Ipv6Addr::LOCALHOST),
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+            ]),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_resolv_conf() {
+        let config = Config::parse(
+            "# comment\n\
+             nameserver 192.0.2.1\n\
+             nameserver fe80::1%eth0\n\
+             nameserver 2001:db8::1\n\
+             nameserver 192.0.2.2\n\
+             nameserver 192.0.2.3\n\
+             domain ignored.example\n\
+             search Example.COM. example.net\n\
+             options ndots:3 timeout:60 attempts:0 rotate\n",
+        );
+        assert_eq!(
+            config.nameservers,
+            [
+                "192.0.2.1:53".parse().unwrap(),
+                "[2001:db8::1]:53".parse().unwrap(),
+                "192.0.2.2:53".parse::<SocketAddr>().unwrap(),
+            ]
+        );
+        assert_eq!(config.search, ["example.com", "example.net"]);
+        assert_eq!(config.ndots, 3);
+        assert_eq!(config.timeout, Duration::from_secs(MAX_TIMEOUT));
+        assert_eq!(config.attempts, 1);
+    }
+
+    #[test]
+    fn parse_empty_resolv_conf() {
+        let config =
+            Config::parse("search a b c d e f g\noptions ndots:99 attempts:9\ndomain d.\n");
+        assert!(config.nameservers.is_empty());
+        assert_eq!(config.search, ["d"]);
+        assert_eq!(config.ndots, MAX_NDOTS);
+        assert_eq!(config.timeout, DEFAULT_TIMEOUT);
+        assert_eq!(config.attempts, MAX_ATTEMPTS);
+    }
+
+    #[test]
+    fn parse_hosts() {
+        let hosts = Hosts::parse(
+            "127.0.0.1 localhost # the loopback\n\
+             # 192.0.2.9 commented\n\
+             192.0.2.1 Host.example alias\n\
+             2001:db8::1 host.example\n\
+             192.0.2.1 host.example\n\
+             not-an-ip other.example\n",
+        );
+        let v4: IpAddr = "192.0.2.1".parse().unwrap();
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert_eq!(hosts.get("host.example"), Some(vec![v4, v6]));
+        assert_eq!(hosts.get("alias"), Some(vec![v4]));
+        assert_eq!(
+            hosts.get("localhost"),
+            Some(vec![Ipv4Addr::LOCALHOST.into()])
+        );
+        assert_eq!(hosts.get("commented"), None);
+        assert_eq!(hosts.get("other.example"), None);
+        assert_eq!(
+            Hosts::empty().get("localhost"),
+            Some(vec![Ipv6Addr::LOCALHOST.into(), Ipv4Addr::LOCALHOST.into()])
+        );
+    }
+}
@@ -0,0 +1,326 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub(super) const TYPE_A: u16 = 1;
+pub(super) const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+
+pub(super) const RCODE_NOERROR: u8 = 0;
+pub(super) const RCODE_NXDOMAIN: u8 = 3;
+
+const HEADER_LEN: usize = 12;
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+
+// Names are at most 255 bytes on the wire, so following more pointers than that means a loop.
+const MAX_POINTERS: usize = 128;
+
+// The part of a response to an `A` or `AAAA` query the resolver uses.
+pub(super) struct Response {
+    pub(super) id: u16,
+    pub(super) truncated: bool,
+    pub(super) rcode: u8,
+    pub(super) addrs: Vec<IpAddr>,
+    // The lowest TTL of the records in `addrs`, in seconds.
+    pub(super) ttl: u32,
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed DNS response")
+}
+
+// Encode a recursive query of type `qtype` for `name`, a name without trailing dot.
+pub(super) fn query(id: u16, name: &str, qtype: u16) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(HEADER_LEN + name.len() + 6);
+    buf.extend_from_slice(&id.to_be_bytes());
+    buf.extend_from_slice(&FLAG_RECURSION_DESIRED.to_be_bytes());
+    buf.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid host name",
+            ));
+        }
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    if buf.len() - HEADER_LEN > 255 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "host name too long",
+        ));
+    }
+    buf.extend_from_slice(&qtype.to_be_bytes());
+    buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(buf)
+}
+
+// Parse a response to the query of type `qtype` for `name`. Responses whose question differs
+// are rejected, the caller matches the id.
+pub(super) fn parse(buf: &[u8], name: &str, qtype: u16) -> io::Result<Response> {
+    let mut r = Reader { buf, pos: 0 };
+    let id = r.u16()?;
+    let flags = r.u16()?;
+    let qdcount = r.u16()?;
+    let ancount = r.u16()?;
+    r.u16()?;
+    r.u16()?;
+    if flags & FLAG_RESPONSE == 0 || qdcount != 1 {
+        return Err(invalid_data());
+    }
+
+    let qname = r.name()?;
+    let (qt, qclass) = (r.u16()?, r.u16()?);
+    if !qname.eq_ignore_ascii_case(name) || qt != qtype || qclass != CLASS_IN {
+        return Err(invalid_data());
+    }
+
+    let mut response = Response {
+        id,
+        truncated: flags & FLAG_TRUNCATED != 0,
+        rcode: (flags & 0xf) as u8,
+        addrs: Vec::new(),
+        ttl: u32::MAX,
+    };
+    // A truncated response may end in the middle of a record.
+    if response.truncated {
+        return Ok(response);
+    }
+    // The answer holds the records of the CNAME chain, if any, before the addresses of its
+    // last name, all of them answering the question.
+    for _ in 0..ancount {
+        r.skip_name()?;
+        let (rtype, class, ttl) = (r.u16()?, r.u16()?, r.u32()?);
+        let rdlength = r.u16()? as usize;
+        let rdata = r.bytes(rdlength)?;
+        let addr = match (rtype, rdata.len()) {
+            _ if rtype != qtype || class != CLASS_IN => continue,
+            (TYPE_A, 4) => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(rdata).unwrap())),
+            (TYPE_AAAA, 16) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(rdata).unwrap())),
+            _ => return Err(invalid_data()),
+        };
+        response.addrs.push(addr);
+        response.ttl = response.ttl.min(ttl);
+    }
+    Ok(response)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + len)
+            .ok_or_else(invalid_data)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn skip_name(&mut self) -> io::Result<()> {
+        self.name().map(drop)
+    }
+
+    // Read a possibly compressed name, returning it without trailing dot.
+    fn name(&mut self) -> io::Result<String> {
+        let mut name = String::new();
+        let mut pos = self.pos;
+        let mut pointers = 0;
+        loop {
+            let len = *self.buf.get(pos).ok_or_else(invalid_data)? as usize;
+            match len {
+                0 => {
+                    if pointers == 0 {
+                        self.pos = pos + 1;
+                    }
+                    return Ok(name);
+                }
+                1..=63 => {
+                    let label = self
+                        .buf
+                        .get(pos + 1..pos + 1 + len)
+                        .ok_or_else(invalid_data)?;
+                    if !name.is_empty() {
+                        name.push('.');
+                    }
+                    name.push_str(&String::from_utf8_lossy(label));
+                    pos += 1 + len;
+                }
+                0xc0..=0xff => {
+                    let low = *self.buf.get(pos + 1).ok_or_else(invalid_data)? as usize;
+                    if pointers == 0 {
+                        self.pos = pos + 2;
+                    }
+                    pointers += 1;
+                    if pointers > MAX_POINTERS {
+                        return Err(invalid_data());
+                    }
+                    pos = (len & 0x3f) << 8 | low;
+                }
+                _ => return Err(invalid_data()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Encode a response to `query(id, name, qtype)`, with the records `answers` of (name, type, ttl,
+    // rdata), the names given as the bytes to write for them.
+    fn encode(
+        id: u16,
+        name: &str,
+        qtype: u16,
+        flags: u16,
+        answers: &[(&[u8], u16, u32, &[u8])],
+    ) -> Vec<u8> {
+        let mut buf = query(id, name, qtype).unwrap();
+        buf[2..4].copy_from_slice(&(FLAG_RESPONSE | flags).to_be_bytes());
+        buf[6..8].copy_from_slice(&(answers.len() as u16).to_be_bytes());
+        for (name, rtype, ttl, rdata) in answers {
+            buf.extend_from_slice(name);
+            buf.extend_from_slice(&rtype.to_be_bytes());
+            buf.extend_from_slice(&CLASS_IN.to_be_bytes());
+            buf.extend_from_slice(&ttl.to_be_bytes());
+            buf.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            buf.extend_from_slice(rdata);
+        }
+        buf
+    }
+
+    // A pointer to the question name, right after the header.
+    const QNAME: &[u8] = &[0xc0, HEADER_LEN as u8];
+
+    #[test]
+    fn query_encodes_labels() {
+        let buf = query(0x1234, "www.example.com", TYPE_A).unwrap();
+        assert_eq!(&buf[..2], &[0x12, 0x34]);
+        assert_eq!(&buf[HEADER_LEN..HEADER_LEN + 5], b"\x03www\x07");
+        assert_eq!(&buf[buf.len() - 4..], &[0, 1, 0, 1]);
+        assert!(query(1, "a..b", TYPE_A).is_err());
+        assert!(query(1, &"a".repeat(64), TYPE_A).is_err());
+    }
+
+    #[test]
+    fn parse_follows_cname_chain() {
+        // The CNAME target is `alias` followed by a pointer to `example.com` in the question.
+        let cname = [&[5][..], b"alias", &[0xc0, HEADER_LEN as u8 + 4]].concat();
+        // The CNAME data follows the question and the fixed fields of its record.
+        let at = query(7, "www.example.com", TYPE_A).unwrap().len() + 12;
+        let alias = [0xc0, at as u8];
+        let buf = encode(
+            7,
+            "www.example.com",
+            TYPE_A,
+            0,
+            &[
+                (QNAME, 5, 300, &cname),
+                (&alias, TYPE_A, 60, &[192, 0, 2, 1]),
+                (&alias, TYPE_A, 120, &[192, 0, 2, 2]),
+            ],
+        );
+        let response = parse(&buf, "WWW.example.com", TYPE_A).unwrap();
+        assert_eq!(response.id, 7);
+        assert_eq!(response.rcode, RCODE_NOERROR);
+        assert!(!response.truncated);
+        assert_eq!(
+            response.addrs,
+            [Ipv4Addr::new(192, 0, 2, 1), Ipv4Addr::new(192, 0, 2, 2)]
+        );
+        assert_eq!(response.ttl, 60);
+    }
+
+    #[test]
+    fn parse_aaaa() {
+        let ip = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+        let buf = encode(
+            1,
+            "example.com",
+            TYPE_AAAA,
+            0,
+            &[(QNAME, TYPE_AAAA, 30, &ip.octets())],
+        );
+        let response = parse(&buf, "example.com", TYPE_AAAA).unwrap();
+        assert_eq!(response.addrs, [IpAddr::V6(ip)]);
+    }
+
+    #[test]
+    fn parse_returns_truncated_and_error_responses() {
+        // A truncated response may end in the middle of a record.
+        let mut buf = encode(1, "example.com", TYPE_A, FLAG_TRUNCATED, &[]);
+        buf[6..8].copy_from_slice(&1u16.to_be_bytes());
+        buf.push(0xc0);
+        let response = parse(&buf, "example.com", TYPE_A).unwrap();
+        assert!(response.truncated);
+        assert!(response.addrs.is_empty());
+
+        let buf = encode(1, "example.com", TYPE_A, RCODE_NXDOMAIN as u16, &[]);
+        assert_eq!(
+            parse(&buf, "example.com", TYPE_A).unwrap().rcode,
+            RCODE_NXDOMAIN
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_questions() {
+        let buf = encode(1, "example.com", TYPE_A, 0, &[]);
+        assert!(parse(&buf, "example.org", TYPE_A).is_err());
+        assert!(parse(&buf, "example.com", TYPE_AAAA).is_err());
+        // A query is not a response.
+        assert!(parse(
+            &query(1, "example.com", TYPE_A).unwrap(),
+            "example.com",
+            TYPE_A
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn parse_rejects_malformed_responses() {
+        let buf = encode(
+            1,
+            "example.com",
+            TYPE_A,
+            0,
+            &[(QNAME, TYPE_A, 60, &[192, 0, 2, 1])],
+        );
+        for len in 0..buf.len() {
+            assert!(parse(&buf[..len], "example.com", TYPE_A).is_err());
+        }
+        // An address of the wrong length.
+        let buf = encode(
+            1,
+            "example.com",
+            TYPE_A,
+            0,
+            &[(QNAME, TYPE_A, 60, &[192, 0, 2])],
+        );
+        assert!(parse(&buf, "example.com", TYPE_A).is_err());
+        // A name pointing to itself.
+        let at = query(1, "example.com", TYPE_A).unwrap().len() as u8;
+        let buf = encode(
+            1,
+            "example.com",
+            TYPE_A,
+            0,
+            &[(&[0xc0, at], TYPE_A, 60, &[192, 0, 2, 1])],
+        );
+        assert!(parse(&buf, "example.com", TYPE_A).is_err());
+    }
+}
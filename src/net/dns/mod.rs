@@ -0,0 +1,363 @@
+mod config;
+mod message;
+
+use std::cell::{OnceCell, RefCell};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::future::poll_fn;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::vec;
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+use super::addr::{self, Target, ToSocketAddrs};
+use super::{TcpStream, UdpSocket};
+use crate::time::{timeout, timeout_at};
+use config::{Config, Hosts};
+use message::{Response, RCODE_NOERROR, RCODE_NXDOMAIN, TYPE_A, TYPE_AAAA};
+
+// Without EDNS, name servers truncate responses to fit in 512 bytes over UDP.
+const MAX_UDP_LEN: usize = 512;
+// Expired entries are only dropped from the cache once it holds this many.
+const MAX_CACHE_LEN: usize = 1024;
+
+thread_local! {
+    static SYSTEM: OnceCell<Rc<Resolver>> = const { OnceCell::new() };
+}
+
+/// Resolve `host` with the resolver of the current thread, configured from
+/// `/etc/resolv.conf` and `/etc/hosts` the first time it is used.
+///
+/// Unlike `std::net::ToSocketAddrs`, this does not block the thread while waiting for the
+/// name servers.
+pub async fn lookup_host<A: ToSocketAddrs>(host: A) -> io::Result<vec::IntoIter<SocketAddr>> {
+    Ok(addr::resolve(host).await?.into_iter())
+}
+
+// Resolve `host` with the resolver of the current thread.
+pub(crate) async fn resolve(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    let resolver = system().await;
+    let ips = resolver.lookup_ip(host).await?;
+    Ok(ips
+        .into_iter()
+        .map(|ip| SocketAddr::new(ip, port))
+        .collect())
+}
+
+// The resolver of the current thread, configured the first time it is used. Lookups racing
+// the first one may read the configuration too, the first resolver created is kept.
+async fn system() -> Rc<Resolver> {
+    if let Some(resolver) = SYSTEM.with(|system| system.get().cloned()) {
+        return resolver;
+    }
+    let resolver = Rc::new(Resolver::system().await);
+    SYSTEM.with(|system| system.get_or_init(|| resolver).clone())
+}
+
+/// An asynchronous DNS stub resolver, sending `A` and `AAAA` queries to name servers over
+/// UDP, and retrying over TCP when a response is truncated. Answers are cached for the
+/// lifetime of their records.
+pub struct Resolver {
+    config: Config,
+    hosts: Hosts,
+    cache: RefCell<HashMap<(String, u16), CacheEntry>>,
+}
+
+struct CacheEntry {
+    addrs: Vec<IpAddr>,
+    expires: Instant,
+}
+
+impl Resolver {
+    /// Create a resolver configured from `/etc/resolv.conf` and `/etc/hosts`, which are read
+    /// once here, without blocking the thread. The `nameserver`, `search`, `domain`, and the
+    /// `ndots`, `timeout` and `attempts` options are supported.
+    pub async fn system() -> Resolver {
+        Resolver::new(Config::load().await, Hosts::load().await)
+    }
+
+    /// Create a resolver querying `nameservers` in order, without search domains or hosts
+    /// file.
+    pub fn with_nameservers(nameservers: Vec<SocketAddr>) -> Resolver {
+        Resolver::new(Config::new(nameservers), Hosts::empty())
+    }
+
+    fn new(config: Config, hosts: Hosts) -> Resolver {
+        Resolver {
+            config,
+            hosts,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Set how long to wait for the answer of a name server, 5 seconds by default.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.config.timeout = timeout;
+    }
+
+    /// Set how many times each name server is tried, 2 by default.
+    pub fn set_attempts(&mut self, attempts: usize) {
+        self.config.attempts = attempts.max(1);
+    }
+
+    /// Resolve `host` to socket addresses.
+    pub async fn lookup_host<A: ToSocketAddrs>(
+        &self,
+        host: A,
+    ) -> io::Result<vec::IntoIter<SocketAddr>> {
+        let addrs = match host.to_target()? {
+            Target::Addrs(addrs) => addrs,
+            Target::Host(host, port) => self
+                .lookup_ip(host)
+                .await?
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, port))
+                .collect(),
+        };
+        Ok(addrs.into_iter())
+    }
+
+    /// Resolve `name` to IP addresses, IPv6 addresses first. Names listed in the hosts file
+    /// are not sent to the name servers.
+    pub async fn lookup_ip(&self, name: &str) -> io::Result<Vec<IpAddr>> {
+        if let Ok(ip) = name.parse() {
+            return Ok(vec![ip]);
+        }
+        let name = name.to_ascii_lowercase();
+        let absolute = name.ends_with('.');
+        let name = name.trim_end_matches('.');
+        if let Some(addrs) = self.hosts.get(name) {
+            return Ok(addrs);
+        }
+
+        let mut last_err = None;
+        for name in self.candidates(name, absolute) {
+            match self.lookup_name(&name).await {
+                Ok(addrs) => return Ok(addrs),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(not_found))
+    }
+
+    // The names to query in turn for `name`, with the search domains appended, before
+    // `name` itself unless it has at least `ndots` dots.
+    fn candidates(&self, name: &str, absolute: bool) -> Vec<String> {
+        if absolute || self.config.search.is_empty() {
+            return vec![name.to_string()];
+        }
+        let mut names: Vec<String> = self
+            .config
+            .search
+            .iter()
+            .map(|domain| format!("{name}.{domain}"))
+            .collect();
+        if name.matches('.').count() >= self.config.ndots {
+            names.insert(0, name.to_string());
+        } else {
+            names.push(name.to_string());
+        }
+        names
+    }
+
+    // Query the name servers for the `AAAA` and `A` records of `name`.
+    async fn lookup_name(&self, name: &str) -> io::Result<Vec<IpAddr>> {
+        let qtypes = [TYPE_AAAA, TYPE_A];
+        let mut answers = qtypes.map(|qtype| self.cached(name, qtype));
+        let mut last_err = None;
+
+        'attempts: for _ in 0..self.config.attempts {
+            for &nameserver in &self.config.nameservers {
+                let pending: Vec<u16> = qtypes
+                    .iter()
+                    .zip(&answers)
+                    .filter(|(_, answer)| answer.is_none())
+                    .map(|(&qtype, _)| qtype)
+                    .collect();
+                if pending.is_empty() {
+                    break 'attempts;
+                }
+                let responses = match self.exchange(nameserver, name, &pending).await {
+                    Ok(responses) => responses,
+                    Err(e) => {
+                        last_err = Some(e);
+                        continue;
+                    }
+                };
+                for (qtype, response) in responses {
+                    let answer = &mut answers[qtypes.iter().position(|&t| t == qtype).unwrap()];
+                    match response.rcode {
+                        RCODE_NOERROR => {
+                            self.cache(name, qtype, &response);
+                            *answer = Some(response.addrs);
+                        }
+                        RCODE_NXDOMAIN => *answer = Some(Vec::new()),
+                        rcode => {
+                            last_err = Some(io::Error::other(format!(
+                                "name server {nameserver} failed with response code {rcode}"
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+
+        let addrs: Vec<IpAddr> = answers.iter().flatten().flatten().copied().collect();
+        if !addrs.is_empty() {
+            Ok(addrs)
+        } else if answers.iter().all(Option::is_some) {
+            Err(not_found())
+        } else {
+            Err(last_err.unwrap_or_else(timed_out))
+        }
+    }
+
+    fn cached(&self, name: &str, qtype: u16) -> Option<Vec<IpAddr>> {
+        let cache = self.cache.borrow();
+        let entry = cache.get(&(name.to_string(), qtype))?;
+        (entry.expires > Instant::now()).then(|| entry.addrs.clone())
+    }
+
+    fn cache(&self, name: &str, qtype: u16, response: &Response) {
+        if response.addrs.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        let mut cache = self.cache.borrow_mut();
+        if cache.len() >= MAX_CACHE_LEN {
+            cache.retain(|_, entry| entry.expires > now);
+        }
+        let entry = CacheEntry {
+            addrs: response.addrs.clone(),
+            expires: now + Duration::from_secs(response.ttl as u64),
+        };
+        cache.insert((name.to_string(), qtype), entry);
+    }
+
+    // Send the queries of types `qtypes` for `name` to `nameserver` over UDP, returning the
+    // responses received within the timeout. Truncated responses are replaced by the full
+    // response over TCP.
+    async fn exchange(
+        &self,
+        nameserver: SocketAddr,
+        name: &str,
+        qtypes: &[u16],
+    ) -> io::Result<Vec<(u16, Response)>> {
+        let local = match nameserver {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let socket = UdpSocket::bind_addr(local)?;
+        socket.connect_addr(nameserver).await?;
+        let mut queries = Vec::with_capacity(qtypes.len());
+        for &qtype in qtypes {
+            let id = random_id();
+            socket.send(&message::query(id, name, qtype)?).await?;
+            queries.push((id, qtype));
+        }
+
+        let deadline = Instant::now() + self.config.timeout;
+        let mut responses: Vec<(u16, Response)> = Vec::with_capacity(queries.len());
+        let mut buf = [0; MAX_UDP_LEN];
+        while responses.len() < queries.len() {
+            let n = match timeout_at(deadline, socket.recv(&mut buf)).await {
+                Ok(Ok(n)) => n,
+                Ok(Err(e)) if responses.is_empty() => return Err(e),
+                Ok(Err(_)) | Err(_) => break,
+            };
+            // Datagrams not answering a pending query are ignored.
+            for &(id, qtype) in &queries {
+                if responses.iter().any(|(answered, _)| *answered == qtype) {
+                    continue;
+                }
+                if let Ok(response) = message::parse(&buf[..n], name, qtype) {
+                    if response.id == id {
+                        responses.push((qtype, response));
+                        break;
+                    }
+                }
+            }
+        }
+        if responses.is_empty() {
+            return Err(timed_out());
+        }
+
+        for (qtype, response) in &mut responses {
+            if response.truncated {
+                *response = self.exchange_tcp(nameserver, name, *qtype).await?;
+            }
+        }
+        Ok(responses)
+    }
+
+    async fn exchange_tcp(
+        &self,
+        nameserver: SocketAddr,
+        name: &str,
+        qtype: u16,
+    ) -> io::Result<Response> {
+        let exchange = async {
+            let mut stream = TcpStream::connect_addr(nameserver).await?;
+            let id = random_id();
+            let query = message::query(id, name, qtype)?;
+            let mut msg = (query.len() as u16).to_be_bytes().to_vec();
+            msg.extend_from_slice(&query);
+            write_all(&mut stream, &msg).await?;
+
+            let mut len = [0; 2];
+            read_exact(&mut stream, &mut len).await?;
+            let mut buf = vec![0; u16::from_be_bytes(len) as usize];
+            read_exact(&mut stream, &mut buf).await?;
+            let response = message::parse(&buf, name, qtype)?;
+            if response.id != id {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "unexpected DNS response id",
+                ));
+            }
+            Ok(response)
+        };
+        timeout(self.config.timeout, exchange)
+            .await
+            .map_err(|_| timed_out())?
+    }
+}
+
+fn random_id() -> u16 {
+    RandomState::new().build_hasher().finish() as u16
+}
+
+fn not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "no address found for host name")
+}
+
+fn timed_out() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "name servers did not answer")
+}
+
+async fn write_all(stream: &mut TcpStream, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        let n = poll_fn(|cx| Pin::new(&mut *stream).poll_write(cx, buf)).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        buf = &buf[n..];
+    }
+    Ok(())
+}
+
+async fn read_exact(stream: &mut TcpStream, mut buf: &mut [u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        let n = poll_fn(|cx| Pin::new(&mut *stream).poll_read(cx, buf)).await?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        buf = &mut buf[n..];
+    }
+    Ok(())
+}
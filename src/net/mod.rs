@@ -1,7 +1,11 @@
+mod addr;
+pub mod dns;
 pub mod tcp;
 pub mod udp;
 pub mod unix;
 
-pub use tcp::{TcpListener, TcpStream};
+pub use addr::ToSocketAddrs;
+pub use dns::lookup_host;
+pub use tcp::{TcpListener, TcpSocket, TcpStream};
 pub use udp::UdpSocket;
 pub use unix::{UnixListener, UnixStream};
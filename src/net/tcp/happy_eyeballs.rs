@@ -0,0 +1,86 @@
+use std::future::{poll_fn, Future};
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use socket2::SockAddr;
+
+use crate::socket::{self, Socket};
+use crate::time::delay_for;
+
+// Connect to the first of `addrs` accepting the connection, as RFC 8305 describes: the
+// addresses are tried alternating between families, starting with the family of the first
+// one, and a new attempt is started every `attempt_delay` or as soon as the last one failed,
+// without waiting for the earlier ones. Attempts still in flight once one succeeded are
+// cancelled.
+pub(super) async fn connect(
+    addrs: Vec<SocketAddr>,
+    attempt_delay: Duration,
+) -> io::Result<socket::Stream> {
+    let addrs = interleave(addrs);
+    let mut next = 0;
+    let mut attempts: Vec<(socket::Stream, SockAddr)> = Vec::new();
+    let mut delay = delay_for(attempt_delay);
+    let mut last_err = None;
+
+    poll_fn(|cx| loop {
+        let mut failed = false;
+        let mut i = 0;
+        while i < attempts.len() {
+            let (stream, addr) = &mut attempts[i];
+            match stream.poll_connect(cx, addr) {
+                Poll::Ready(Ok(())) => return Poll::Ready(Ok(attempts.swap_remove(i).0)),
+                Poll::Ready(Err(e)) => {
+                    attempts.swap_remove(i);
+                    last_err = Some(e);
+                    failed = true;
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        if next == addrs.len() {
+            if attempts.is_empty() {
+                return Poll::Ready(Err(last_err.take().unwrap_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "could not resolve to any address",
+                    )
+                })));
+            }
+            return Poll::Pending;
+        }
+        if !attempts.is_empty() && !failed && Pin::new(&mut delay).poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        let addr = addrs[next];
+        next += 1;
+        match Socket::new(addr, libc::SOCK_STREAM) {
+            Ok(socket) => attempts.push((socket::Stream::new(socket), SockAddr::from(addr))),
+            Err(e) => last_err = Some(e),
+        }
+        delay.reset(Instant::now() + attempt_delay);
+    })
+    .await
+}
+
+// Order `addrs` alternating between IPv6 and IPv4 addresses, starting with the family of the
+// first address and keeping the order of the addresses of each family.
+fn interleave(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let first_v6 = addrs.first().is_some_and(|addr| addr.is_ipv6());
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_v6);
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+    let mut addrs = Vec::new();
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return addrs,
+            (a, b) => addrs.extend(a.into_iter().chain(b)),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt;
+use std::mem;
+use std::time::Duration;
+
+use crate::socket::TcpInfoRaw;
+
+/// Statistics the kernel keeps about a TCP connection, as returned by `TCP_INFO`.
+///
+/// Fields added in later kernel versions are `None` when the running kernel does not
+/// report them.
+#[derive(Clone)]
+pub struct TcpInfo {
+    raw: TcpInfoRaw,
+    len: usize,
+}
+
+/// The state of a TCP connection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TcpState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+}
+
+impl TcpInfo {
+    pub(crate) fn new(raw: TcpInfoRaw, len: usize) -> TcpInfo {
+        TcpInfo { raw, len }
+    }
+
+    // Whether the kernel filled in the field at `offset`, of type `T`.
+    fn has<T>(&self, offset: usize) -> bool {
+        offset + mem::size_of::<T>() <= self.len
+    }
+
+    /// The state of the connection, `None` for states unknown to this crate.
+    pub fn state(&self) -> Option<TcpState> {
+        let state = match self.raw.state as libc::c_int {
+            1 => TcpState::Established,
+            2 => TcpState::SynSent,
+            3 => TcpState::SynRecv,
+            4 => TcpState::FinWait1,
+            5 => TcpState::FinWait2,
+            6 => TcpState::TimeWait,
+            7 => TcpState::Close,
+            8 => TcpState::CloseWait,
+            9 => TcpState::LastAck,
+            10 => TcpState::Listen,
+            11 => TcpState::Closing,
+            _ => return None,
+        };
+        Some(state)
+    }
+
+    /// The smoothed round trip time.
+    pub fn rtt(&self) -> Duration {
+        Duration::from_micros(self.raw.rtt as u64)
+    }
+
+    /// The variation of the round trip time.
+    pub fn rttvar(&self) -> Duration {
+        Duration::from_micros(self.raw.rttvar as u64)
+    }
+
+    /// The retransmission timeout.
+    pub fn rto(&self) -> Duration {
+        Duration::from_micros(self.raw.rto as u64)
+    }
+
+    /// The congestion window, in segments.
+    pub fn cwnd(&self) -> u32 {
+        self.raw.snd_cwnd
+    }
+
+    /// The number of segments sent but not acknowledged yet.
+    pub fn unacked(&self) -> u32 {
+        self.raw.unacked
+    }
+
+    /// The number of retransmissions of the oldest unacknowledged segment.
+    pub fn retransmits(&self) -> u8 {
+        self.raw.retransmits
+    }
+
+    /// The number of segments retransmitted over the life of the connection.
+    pub fn total_retransmits(&self) -> u32 {
+        self.raw.total_retrans
+    }
+
+    /// The number of bytes acknowledged by the peer, since Linux 4.1.
+    pub fn bytes_acked(&self) -> Option<u64> {
+        self.has::<u64>(mem::offset_of!(TcpInfoRaw, bytes_acked))
+            .then_some(self.raw.bytes_acked)
+    }
+
+    /// The most recent estimate of the delivery rate, in bytes per second, since Linux 4.9.
+    pub fn delivery_rate(&self) -> Option<u64> {
+        self.has::<u64>(mem::offset_of!(TcpInfoRaw, delivery_rate))
+            .then_some(self.raw.delivery_rate)
+    }
+}
+
+impl fmt::Debug for TcpInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpInfo")
+            .field("state", &self.state())
+            .field("rtt", &self.rtt())
+            .field("rttvar", &self.rttvar())
+            .field("cwnd", &self.cwnd())
+            .field("retransmits", &self.retransmits())
+            .field("total_retransmits", &self.total_retransmits())
+            .field("bytes_acked", &self.bytes_acked())
+            .field("delivery_rate", &self.delivery_rate())
+            .finish_non_exhaustive()
+    }
+}
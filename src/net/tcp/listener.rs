@@ -1,21 +1,36 @@
 use std::future::poll_fn;
 use std::io;
-use std::net::{self, SocketAddr, ToSocketAddrs};
+use std::net::{self, SocketAddr};
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use std::pin::Pin;
 use std::task::{ready, Context, Poll};
 
+use futures_core::stream::Stream;
 use socket2::SockAddr;
 
 use super::stream::TcpStream;
-use crate::socket::{self, Socket};
+use crate::net::addr::{self, ToSocketAddrs};
+use crate::socket::{self, Socket, SocketStorage};
 
 pub struct TcpListener {
     inner: socket::Listener,
 }
 
+fn to_socket_addr(socketaddr: SocketStorage) -> io::Result<SocketAddr> {
+    let (_, addr) = unsafe {
+        SockAddr::try_init(move |addr_storage, len| {
+            *addr_storage = socketaddr.storage.to_owned();
+            *len = socketaddr.socklen;
+            Ok(())
+        })?
+    };
+    addr.as_socket()
+        .ok_or_else(|| io::Error::other("Could not get socket IP address"))
+}
+
 impl TcpListener {
-    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<TcpListener> {
-        let addrs = addr.to_socket_addrs()?;
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<TcpListener> {
+        let addrs = addr::resolve(addr).await?;
         let mut last_err = None;
 
         for addr in addrs {
@@ -52,19 +67,43 @@ impl TcpListener {
         poll_fn(|cx| self.poll_accept2(cx)).await
     }
 
+    /// Accept a connection as a direct descriptor, a slot of the registered file table
+    /// allocated by the kernel, without creating a file descriptor for it.
+    ///
+    /// The stream supports all io_uring backed I/O, but has no file descriptor: `as_raw_fd`
+    /// returns -1, and methods issuing system calls on it, like `peer_addr` or `set_nodelay`,
+    /// fail with `ErrorKind::Unsupported`.
+    pub async fn accept_direct(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        poll_fn(|cx| self.poll_accept_direct(cx)).await
+    }
+
+    /// Return a stream of the accepted connections, served by a single multishot accept
+    /// like [`accept2`](TcpListener::accept2), with which it shares its state.
+    ///
+    /// Like `std::net::Incoming`, the stream never ends: errors are yielded, and the next
+    /// poll arms a new multishot accept.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+
+    /// Install the listener in the registered file table of the runtime, so that later
+    /// operations skip the per-operation file descriptor lookup in the kernel. The file
+    /// descriptor stays open for operations that need it.
+    pub fn register_fixed(&mut self) -> io::Result<()> {
+        self.inner.get_mut().register()
+    }
+
     pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
         let (socket, socketaddr) = ready!(self.inner.poll_accept(cx))?;
-        let (_, addr) = unsafe {
-            SockAddr::try_init(move |addr_storage, len| {
-                *addr_storage = socketaddr.storage.to_owned();
-                *len = socketaddr.socklen;
-                Ok(())
-            })?
-        };
-        let socket_addr = addr.as_socket().ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Could not get socket IP address")
-        })?;
-        Poll::Ready(Ok((socket.into(), socket_addr)))
+        Poll::Ready(Ok((socket.into(), to_socket_addr(socketaddr)?)))
+    }
+
+    pub fn poll_accept_direct(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+        let (socket, socketaddr) = ready!(self.inner.poll_accept_direct(cx))?;
+        Poll::Ready(Ok((socket.into(), to_socket_addr(socketaddr)?)))
     }
 
     pub fn poll_accept2(&self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
@@ -76,6 +115,45 @@ impl TcpListener {
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         self.inner.local_addr()
     }
+
+    /// Set the time to live of sent packets, the hop limit for IPv6 sockets.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.get_ref().ttl()
+    }
+
+    /// Only accept IPv6 connections on an IPv6 socket, with `IPV6_V6ONLY`. This only
+    /// takes effect for sockets not bound yet, see [`TcpSocket`](super::TcpSocket).
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.inner.get_ref().set_only_v6(only_v6)
+    }
+
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.inner.get_ref().only_v6()
+    }
+
+    /// Get and clear the pending error of the socket, with `SO_ERROR`.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+}
+
+/// A stream of the connections accepted by a [`TcpListener`], see
+/// [`TcpListener::incoming`].
+pub struct Incoming<'a> {
+    listener: &'a TcpListener,
+}
+
+impl Stream for Incoming<'_> {
+    type Item = io::Result<TcpStream>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let res = ready!(self.listener.inner.poll_accept2(cx));
+        Poll::Ready(Some(res.map(TcpStream::from)))
+    }
 }
 
 impl AsRawFd for TcpListener {
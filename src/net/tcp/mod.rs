@@ -1,5 +1,12 @@
+mod happy_eyeballs;
+pub mod info;
 pub mod listener;
+pub mod socket;
+pub mod split;
 pub mod stream;
 
-pub use listener::TcpListener;
+pub use info::{TcpInfo, TcpState};
+pub use listener::{Incoming, TcpListener};
+pub use socket::TcpSocket;
+pub use split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, ReuniteError, WriteHalf};
 pub use stream::TcpStream;
@@ -0,0 +1,125 @@
+use std::future::poll_fn;
+use std::io;
+use std::net::{self, SocketAddr};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+use socket2::{Domain, SockAddr};
+
+use super::{TcpListener, TcpStream};
+use crate::socket::{self, Socket};
+
+/// A TCP socket that has not been connected or turned into a listener yet, to set options
+/// that must be set before, or to bind it to a local address.
+///
+/// Unlike [`TcpListener::bind`], no option is set by default.
+pub struct TcpSocket {
+    inner: socket2::Socket,
+}
+
+impl TcpSocket {
+    /// Create an IPv4 TCP socket.
+    pub fn new_v4() -> io::Result<TcpSocket> {
+        TcpSocket::new(Domain::IPV4)
+    }
+
+    /// Create an IPv6 TCP socket.
+    pub fn new_v6() -> io::Result<TcpSocket> {
+        TcpSocket::new(Domain::IPV6)
+    }
+
+    fn new(domain: Domain) -> io::Result<TcpSocket> {
+        let socket_type = libc::SOCK_STREAM | libc::SOCK_CLOEXEC;
+        Ok(TcpSocket {
+            inner: socket2::Socket::new(domain, socket_type.into(), None)?,
+        })
+    }
+
+    /// Allow binding to an address in use by a socket in `TIME_WAIT`, with `SO_REUSEADDR`.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.inner.set_reuse_address(reuseaddr)
+    }
+
+    /// Allow several sockets to bind the same address, with `SO_REUSEPORT`, the kernel
+    /// balancing incoming connections between the listeners.
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        self.inner.set_reuse_port(reuseport)
+    }
+
+    /// Only accept IPv6 connections on an IPv6 socket, with `IPV6_V6ONLY`.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.inner.set_only_v6(only_v6)
+    }
+
+    /// Set the size of the send buffer with `SO_SNDBUF`, the kernel doubles it.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Set the size of the receive buffer with `SO_RCVBUF`, the kernel doubles it.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Only send and receive through the network interface named `interface`, with
+    /// `SO_BINDTODEVICE`, or through any interface with `None`.
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        self.inner.bind_device(interface)
+    }
+
+    /// Set the type of service of sent packets, `IP_TOS` for IPv4 sockets and `IPV6_TCLASS`
+    /// for IPv6 sockets.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        if self.inner.domain()? == Domain::IPV6 {
+            self.inner.set_tclass_v6(tos)
+        } else {
+            self.inner.set_tos(tos)
+        }
+    }
+
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<()> {
+        self.inner.bind(&addr.into())
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner
+            .local_addr()?
+            .as_socket()
+            .ok_or_else(|| io::ErrorKind::InvalidInput.into())
+    }
+
+    /// Connect the socket to `addr`.
+    pub async fn connect(self, addr: SocketAddr) -> io::Result<TcpStream> {
+        let socket = Socket::from(self.inner.into_raw_fd());
+        let mut stream = socket::Stream::new(socket);
+        poll_fn(|cx| stream.poll_connect(cx, &SockAddr::from(addr))).await?;
+        Ok(TcpStream::from_inner(stream))
+    }
+
+    /// Turn the socket into a listener, with at most `backlog` connections waiting to be
+    /// accepted.
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        let backlog = backlog.min(i32::MAX as u32) as i32;
+        self.inner.listen(backlog)?;
+        TcpListener::from_std(net::TcpListener::from(self.inner))
+    }
+}
+
+impl AsRawFd for TcpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl FromRawFd for TcpSocket {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        TcpSocket {
+            inner: socket2::Socket::from_raw_fd(fd),
+        }
+    }
+}
+
+impl IntoRawFd for TcpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.inner.into_raw_fd()
+    }
+}
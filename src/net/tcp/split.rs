@@ -0,0 +1,6 @@
+use std::net::SocketAddr;
+
+use super::TcpStream;
+use crate::socket::{stream::stream_halves, Socket};
+
+stream_halves!(TcpStream, SocketAddr, Socket::local_addr, Socket::peer_addr);
@@ -1,13 +1,20 @@
 use std::future::poll_fn;
-use std::io;
-use std::net::{self, SocketAddr, ToSocketAddrs};
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::io::{self, IoSlice, IoSliceMut};
+use std::net::{self, SocketAddr};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
 use socket2::SockAddr;
 
+use super::happy_eyeballs;
+use super::info::TcpInfo;
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use crate::driver::{Fd, Op};
+use crate::fs::File;
+use crate::net::addr::{self, ToSocketAddrs};
 use crate::socket::{self, Socket};
 
 pub struct TcpStream {
@@ -16,13 +23,13 @@ pub struct TcpStream {
 
 impl TcpStream {
     pub fn from_std(stream: net::TcpStream) -> TcpStream {
-        let socket = unsafe { Socket::from_raw_fd(stream.as_raw_fd()) };
+        let socket = unsafe { Socket::from_raw_fd(stream.into_raw_fd()) };
         TcpStream {
             inner: socket::Stream::new(socket),
         }
     }
 
-    async fn connect_addr(addr: SocketAddr) -> io::Result<TcpStream> {
+    pub(crate) async fn connect_addr(addr: SocketAddr) -> io::Result<TcpStream> {
         let socket = Socket::new(addr, libc::SOCK_STREAM)?;
         let mut stream = socket::Stream::new(socket);
         poll_fn(|cx| stream.poll_connect(cx, &SockAddr::from(addr))).await?;
@@ -30,7 +37,7 @@ impl TcpStream {
     }
 
     pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<TcpStream> {
-        let addrs = addr.to_socket_addrs()?;
+        let addrs = addr::resolve(addr).await?;
 
         let mut last_err = None;
         for addr in addrs {
@@ -48,6 +55,22 @@ impl TcpStream {
         }))
     }
 
+    /// Connect to `addr` racing the resolved addresses, as described by the Happy Eyeballs
+    /// algorithm of RFC 8305.
+    ///
+    /// The addresses are tried alternating between IPv6 and IPv4, in the resolved order
+    /// otherwise. A new attempt starts every `attempt_delay`, 250 milliseconds being the
+    /// recommended value, or as soon as the previous attempt failed. The first connection
+    /// established is returned and the other attempts are cancelled.
+    pub async fn connect_happy_eyeballs<A: ToSocketAddrs>(
+        addr: A,
+        attempt_delay: Duration,
+    ) -> io::Result<TcpStream> {
+        let addrs = addr::resolve(addr).await?;
+        let stream = happy_eyeballs::connect(addrs, attempt_delay).await?;
+        Ok(TcpStream { inner: stream })
+    }
+
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
         self.inner.get_ref().local_addr()
     }
@@ -59,6 +82,198 @@ impl TcpStream {
     pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
         self.inner.get_ref().set_nodelay(nodelay)
     }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.get_ref().nodelay()
+    }
+
+    /// Enable sending keepalive probes on an idle connection, with `SO_KEEPALIVE`.
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.inner.get_ref().set_keepalive(keepalive)
+    }
+
+    pub fn keepalive(&self) -> io::Result<bool> {
+        self.inner.get_ref().keepalive()
+    }
+
+    /// Set how long the connection stays idle before keepalive probes are sent, with
+    /// `TCP_KEEPIDLE`. The duration is rounded down to seconds.
+    pub fn set_keepalive_idle(&self, idle: Duration) -> io::Result<()> {
+        self.inner.get_ref().set_keepalive_idle(idle)
+    }
+
+    pub fn keepalive_idle(&self) -> io::Result<Duration> {
+        self.inner.get_ref().keepalive_idle()
+    }
+
+    /// Set the time between keepalive probes, with `TCP_KEEPINTVL`. The duration is rounded
+    /// down to seconds.
+    pub fn set_keepalive_interval(&self, interval: Duration) -> io::Result<()> {
+        self.inner.get_ref().set_keepalive_interval(interval)
+    }
+
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        self.inner.get_ref().keepalive_interval()
+    }
+
+    /// Set the number of unanswered keepalive probes after which the connection is dropped,
+    /// with `TCP_KEEPCNT`.
+    pub fn set_keepalive_retries(&self, retries: u32) -> io::Result<()> {
+        self.inner.get_ref().set_keepalive_retries(retries)
+    }
+
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        self.inner.get_ref().keepalive_retries()
+    }
+
+    /// Set how long closing the socket waits for unsent data to be sent, with `SO_LINGER`.
+    /// With `Some(Duration::ZERO)`, closing resets the connection. The duration is rounded
+    /// down to seconds.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        self.inner.get_ref().set_linger(linger)
+    }
+
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        self.inner.get_ref().linger()
+    }
+
+    /// Set the time to live of sent packets, the hop limit for IPv6 sockets.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.get_ref().ttl()
+    }
+
+    /// Set how long sent data may stay unacknowledged before the connection is dropped,
+    /// with `TCP_USER_TIMEOUT`. Zero uses the system default.
+    pub fn set_user_timeout(&self, timeout: Duration) -> io::Result<()> {
+        self.inner.get_ref().set_user_timeout(timeout)
+    }
+
+    pub fn user_timeout(&self) -> io::Result<Duration> {
+        self.inner.get_ref().user_timeout()
+    }
+
+    /// Send acknowledgments immediately instead of delaying them, with `TCP_QUICKACK`. The
+    /// kernel may turn this off again on its own, so it is usually set after each read.
+    pub fn set_quickack(&self, quickack: bool) -> io::Result<()> {
+        self.inner.get_ref().set_quickack(quickack)
+    }
+
+    pub fn quickack(&self) -> io::Result<bool> {
+        self.inner.get_ref().quickack()
+    }
+
+    /// Limit the amount of unsent data in the send buffer, with `TCP_NOTSENT_LOWAT`.
+    pub fn set_notsent_lowat(&self, lowat: u32) -> io::Result<()> {
+        self.inner.get_ref().set_notsent_lowat(lowat)
+    }
+
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        self.inner.get_ref().notsent_lowat()
+    }
+
+    /// Set the congestion control algorithm, like `"cubic"` or `"bbr"`, with
+    /// `TCP_CONGESTION`.
+    pub fn set_congestion(&self, algorithm: &str) -> io::Result<()> {
+        self.inner.get_ref().set_congestion(algorithm)
+    }
+
+    pub fn congestion(&self) -> io::Result<String> {
+        self.inner.get_ref().congestion()
+    }
+
+    /// Set the size of the send buffer with `SO_SNDBUF`, the kernel doubles it.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.get_ref().set_send_buffer_size(size)
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.get_ref().send_buffer_size()
+    }
+
+    /// Set the size of the receive buffer with `SO_RCVBUF`, the kernel doubles it.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.get_ref().set_recv_buffer_size(size)
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.get_ref().recv_buffer_size()
+    }
+
+    /// Get the statistics the kernel keeps about the connection, with `TCP_INFO`.
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        let (raw, len) = self.inner.get_ref().tcp_info()?;
+        Ok(TcpInfo::new(raw, len))
+    }
+
+    /// Get and clear the pending error of the socket, with `SO_ERROR`.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Send `len` bytes of `file` starting at `offset` without copying them through
+    /// userspace, returning the number of bytes sent. Fewer bytes are sent if the file ends
+    /// first, or if an error occurs after some bytes were sent. The file cursor is not
+    /// affected. Buffered writes are flushed first.
+    pub async fn send_file(&mut self, file: &File, offset: u64, len: u64) -> io::Result<u64> {
+        poll_fn(|cx| self.inner.poll_flush(cx)).await?;
+        crate::io::send_file(self.inner.get_ref().target(), file, offset, len).await
+    }
+
+    /// Send `buf` without copying it to the kernel, returning the number of bytes sent along
+    /// with the buffer. The buffer is only handed back once the kernel no longer uses it,
+    /// which makes this worthwhile for large buffers. Buffered writes are flushed first.
+    pub async fn send_zc(&mut self, buf: Vec<u8>) -> io::Result<(usize, Vec<u8>)> {
+        poll_fn(|cx| self.inner.poll_flush(cx)).await?;
+        Op::send_zc(self.target(), buf, None)?.await
+    }
+
+    /// Install the socket in the registered file table of the runtime, so that later
+    /// operations skip the per-operation file descriptor lookup in the kernel. The file
+    /// descriptor stays open for operations that need it.
+    pub fn register_fixed(&mut self) -> io::Result<()> {
+        self.inner.get_mut().register()
+    }
+
+    /// Split the stream into a read half and a write half borrowing it, which can be used
+    /// concurrently, for example in a `join` of a read loop and a write loop.
+    ///
+    /// With `AsyncReadExt` from the futures crate in scope, `stream.split()` resolves to its
+    /// by-value `split`, call `TcpStream::split(&mut stream)` instead.
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split::split(&mut self.inner)
+    }
+
+    /// Split the stream into owned read and write halves, which can be moved to different
+    /// tasks. The halves can be put back together with `reunite`.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::into_split(self.inner)
+    }
+
+    /// Buffer writes smaller than `capacity` bytes, and send them together once the buffer
+    /// fills up or the stream is flushed, like `TCP_CORK` does in the kernel. Larger writes
+    /// are sent directly once the buffer is empty. A capacity of 0, the default, sends each
+    /// write on its own.
+    ///
+    /// Buffered bytes are lost if the stream is dropped without being flushed or closed.
+    pub fn set_write_buffer(&mut self, capacity: usize) {
+        self.inner.set_write_buffer(capacity)
+    }
+
+    pub(super) fn from_inner(inner: socket::Stream) -> TcpStream {
+        TcpStream { inner }
+    }
+
+    pub(crate) fn target(&self) -> Fd {
+        self.inner.get_ref().target()
+    }
+
+    pub(crate) fn take_buffered(&mut self) -> Vec<u8> {
+        self.inner.take_buffered()
+    }
 }
 
 impl AsyncBufRead for TcpStream {
@@ -79,6 +294,14 @@ impl AsyncRead for TcpStream {
     ) -> Poll<io::Result<usize>> {
         self.get_mut().inner.poll_read(cx, buf)
     }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().inner.poll_read_vectored(cx, bufs)
+    }
 }
 
 impl AsyncWrite for TcpStream {
@@ -86,8 +309,16 @@ impl AsyncWrite for TcpStream {
         self.get_mut().inner.poll_write(cx, buf)
     }
 
-    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
-        Poll::Ready(Ok(()))
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        self.get_mut().inner.poll_flush(cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
@@ -104,6 +335,8 @@ impl From<Socket> for TcpStream {
 }
 
 impl AsRawFd for TcpStream {
+    /// Returns -1 for streams accepted with
+    /// [`accept_direct`](super::TcpListener::accept_direct), which have no file descriptor.
     fn as_raw_fd(&self) -> RawFd {
         self.inner.get_ref().as_raw_fd()
     }
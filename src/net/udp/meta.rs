@@ -0,0 +1,221 @@
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, SystemTime};
+
+use crate::socket::cmsg::{self, Cmsgs};
+
+/// The explicit congestion notification codepoint of a datagram, the two low bits of its
+/// TOS byte or traffic class, see RFC 3168.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ecn {
+    /// Not ECN-capable transport.
+    NotEct,
+    /// ECN-capable transport, codepoint 1.
+    Ect1,
+    /// ECN-capable transport, codepoint 0.
+    Ect0,
+    /// Congestion experienced.
+    Ce,
+}
+
+impl Ecn {
+    fn from_tos(tos: u8) -> Ecn {
+        match tos & 0b11 {
+            0b00 => Ecn::NotEct,
+            0b01 => Ecn::Ect1,
+            0b10 => Ecn::Ect0,
+            _ => Ecn::Ce,
+        }
+    }
+
+    fn bits(self) -> u8 {
+        match self {
+            Ecn::NotEct => 0b00,
+            Ecn::Ect1 => 0b01,
+            Ecn::Ect0 => 0b10,
+            Ecn::Ce => 0b11,
+        }
+    }
+}
+
+/// The ancillary data of a datagram received with
+/// [`UdpSocket::recv_msg`](super::UdpSocket::recv_msg). Apart from the source address, each
+/// part is only present if the socket was set to receive it.
+#[derive(Debug, Clone)]
+pub struct RecvMeta {
+    source: SocketAddr,
+    destination: Option<IpAddr>,
+    interface: Option<u32>,
+    timestamp: Option<SystemTime>,
+    tos: Option<u8>,
+    ttl: Option<u8>,
+}
+
+impl RecvMeta {
+    // The space the control messages of all the parts take.
+    pub(crate) fn control_len() -> usize {
+        cmsg::space(mem::size_of::<libc::in6_pktinfo>())
+            + cmsg::space(mem::size_of::<libc::timespec>())
+            + 2 * cmsg::space(mem::size_of::<libc::c_int>())
+    }
+
+    pub(crate) fn new(source: SocketAddr, control: Option<&Cmsgs>) -> RecvMeta {
+        let mut meta = RecvMeta {
+            source,
+            destination: None,
+            interface: None,
+            timestamp: None,
+            tos: None,
+            ttl: None,
+        };
+        let Some(control) = control else {
+            return meta;
+        };
+
+        if let Some(info) = control.get::<libc::in_pktinfo>(libc::IPPROTO_IP, libc::IP_PKTINFO) {
+            let addr = Ipv4Addr::from(info.ipi_addr.s_addr.to_ne_bytes());
+            meta.destination = Some(IpAddr::V4(addr));
+            meta.interface = Some(info.ipi_ifindex as u32);
+        }
+        if let Some(info) = control.get::<libc::in6_pktinfo>(libc::IPPROTO_IPV6, libc::IPV6_PKTINFO)
+        {
+            let addr = Ipv6Addr::from(info.ipi6_addr.s6_addr);
+            meta.destination = Some(IpAddr::V6(addr));
+            meta.interface = Some(info.ipi6_ifindex);
+        }
+        if let Some(ts) = control.get::<libc::timespec>(libc::SOL_SOCKET, libc::SO_TIMESTAMPNS) {
+            let since_epoch = Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32);
+            meta.timestamp = Some(SystemTime::UNIX_EPOCH + since_epoch);
+        }
+        // The TOS byte is a single byte, unlike the traffic class.
+        meta.tos = control
+            .get::<u8>(libc::IPPROTO_IP, libc::IP_TOS)
+            .or_else(|| {
+                control
+                    .get::<libc::c_int>(libc::IPPROTO_IPV6, libc::IPV6_TCLASS)
+                    .map(|tclass| tclass as u8)
+            });
+        meta.ttl = control
+            .get::<libc::c_int>(libc::IPPROTO_IP, libc::IP_TTL)
+            .or_else(|| control.get::<libc::c_int>(libc::IPPROTO_IPV6, libc::IPV6_HOPLIMIT))
+            .map(|ttl| ttl as u8);
+        meta
+    }
+
+    pub fn source(&self) -> SocketAddr {
+        self.source
+    }
+
+    /// The destination address of the datagram, the local address it was sent to, which a
+    /// socket bound to the unspecified address otherwise does not know. Received with
+    /// [`set_recv_pktinfo`](super::UdpSocket::set_recv_pktinfo).
+    pub fn destination(&self) -> Option<IpAddr> {
+        self.destination
+    }
+
+    /// The index of the interface the datagram arrived on, received along with the
+    /// destination address.
+    pub fn interface(&self) -> Option<u32> {
+        self.interface
+    }
+
+    /// When the kernel received the datagram, with
+    /// [`set_recv_timestamps`](super::UdpSocket::set_recv_timestamps).
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        self.timestamp
+    }
+
+    /// The TOS byte of an IPv4 datagram or the traffic class of an IPv6 datagram, with
+    /// [`set_recv_tos`](super::UdpSocket::set_recv_tos).
+    pub fn tos(&self) -> Option<u8> {
+        self.tos
+    }
+
+    /// The ECN codepoint, the two low bits of [`tos`](RecvMeta::tos).
+    pub fn ecn(&self) -> Option<Ecn> {
+        self.tos.map(Ecn::from_tos)
+    }
+
+    /// The TTL of an IPv4 datagram or the hop limit of an IPv6 datagram, with
+    /// [`set_recv_ttl`](super::UdpSocket::set_recv_ttl).
+    pub fn ttl(&self) -> Option<u8> {
+        self.ttl
+    }
+}
+
+/// The ancillary data to send a datagram with, see
+/// [`UdpSocket::send_msg`](super::UdpSocket::send_msg).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SendMeta {
+    source: Option<IpAddr>,
+    ecn: Option<Ecn>,
+}
+
+impl SendMeta {
+    pub fn new() -> SendMeta {
+        SendMeta::default()
+    }
+
+    /// Send the datagram from `source`, one of the local addresses, with `IP_PKTINFO` or
+    /// `IPV6_PKTINFO`. This lets a socket bound to the unspecified address answer from the
+    /// address a request was sent to.
+    pub fn source(mut self, source: IpAddr) -> SendMeta {
+        self.source = Some(source);
+        self
+    }
+
+    /// Mark the datagram with `ecn`. The TOS byte or traffic class of the datagram is set to
+    /// the codepoint, in place of the one of the socket.
+    pub fn ecn(mut self, ecn: Ecn) -> SendMeta {
+        self.ecn = Some(ecn);
+        self
+    }
+
+    // The control messages to send a datagram to `target` with.
+    pub(crate) fn control(&self, target: SocketAddr) -> io::Result<Option<Cmsgs>> {
+        if self.source.is_none() && self.ecn.is_none() {
+            return Ok(None);
+        }
+        let mut control = Cmsgs::with_capacity(
+            cmsg::space(mem::size_of::<libc::in6_pktinfo>())
+                + cmsg::space(mem::size_of::<libc::c_int>()),
+        );
+        match (self.source, target) {
+            (None, _) => {}
+            (Some(IpAddr::V4(source)), SocketAddr::V4(_)) => {
+                let info = libc::in_pktinfo {
+                    ipi_ifindex: 0,
+                    ipi_spec_dst: libc::in_addr {
+                        s_addr: u32::from_ne_bytes(source.octets()),
+                    },
+                    ipi_addr: libc::in_addr { s_addr: 0 },
+                };
+                control.push(libc::IPPROTO_IP, libc::IP_PKTINFO, info);
+            }
+            (Some(IpAddr::V6(source)), SocketAddr::V6(_)) => {
+                let info = libc::in6_pktinfo {
+                    ipi6_addr: libc::in6_addr {
+                        s6_addr: source.octets(),
+                    },
+                    ipi6_ifindex: 0,
+                };
+                control.push(libc::IPPROTO_IPV6, libc::IPV6_PKTINFO, info);
+            }
+            (Some(_), _) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "source and target addresses of different families",
+                ));
+            }
+        }
+        if let Some(ecn) = self.ecn {
+            let tos = ecn.bits() as libc::c_int;
+            match target {
+                SocketAddr::V4(_) => control.push(libc::IPPROTO_IP, libc::IP_TOS, tos),
+                SocketAddr::V6(_) => control.push(libc::IPPROTO_IPV6, libc::IPV6_TCLASS, tos),
+            }
+        }
+        Ok(Some(control))
+    }
+}
@@ -0,0 +1,5 @@
+pub mod meta;
+pub mod socket;
+
+pub use meta::{Ecn, RecvMeta, SendMeta};
+pub use socket::{RecvStream, UdpSocket};
@@ -0,0 +1,438 @@
+use std::future::poll_fn;
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::stream::Stream;
+use socket2::SockAddr;
+
+use super::meta::{RecvMeta, SendMeta};
+use crate::buffer::Buf;
+use crate::driver::Op;
+use crate::net::addr::{self, ToSocketAddrs};
+use crate::socket::cmsg::{self, Cmsgs};
+use crate::socket::{packet, Packet, Socket};
+
+// The most datagrams of `send_many` submitted at once, well within the submission queue.
+const MAX_BATCH: usize = 128;
+
+pub struct UdpSocket {
+    inner: Packet,
+}
+
+impl UdpSocket {
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<UdpSocket> {
+        let addrs = addr::resolve(addr).await?;
+        let mut last_err = None;
+
+        for addr in addrs {
+            match UdpSocket::bind_addr(addr) {
+                Ok(socket) => return Ok(socket),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "could not resolve to any address",
+            )
+        }))
+    }
+
+    pub(crate) fn bind_addr(addr: SocketAddr) -> io::Result<UdpSocket> {
+        let socket = Socket::bind(addr, libc::SOCK_DGRAM)?;
+        Ok(UdpSocket {
+            inner: Packet::new(socket),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.get_ref().local_addr()
+    }
+
+    /// Set the time to live of sent packets, the hop limit for IPv6 sockets.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_ttl(ttl)
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.get_ref().ttl()
+    }
+
+    /// Set the size of the send buffer with `SO_SNDBUF`, the kernel doubles it.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.get_ref().set_send_buffer_size(size)
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.get_ref().send_buffer_size()
+    }
+
+    /// Set the size of the receive buffer with `SO_RCVBUF`, the kernel doubles it.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.get_ref().set_recv_buffer_size(size)
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.get_ref().recv_buffer_size()
+    }
+
+    /// Allow sending to broadcast addresses, with `SO_BROADCAST`.
+    pub fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.inner.get_ref().set_broadcast(broadcast)
+    }
+
+    pub fn broadcast(&self) -> io::Result<bool> {
+        self.inner.get_ref().broadcast()
+    }
+
+    /// Join the IPv4 multicast group `multiaddr` on the interface with the address
+    /// `interface`, or an interface chosen by the system if it is unspecified.
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner
+            .get_ref()
+            .join_multicast_v4(*multiaddr, *interface)
+    }
+
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner
+            .get_ref()
+            .leave_multicast_v4(*multiaddr, *interface)
+    }
+
+    /// Join the IPv6 multicast group `multiaddr` on the interface of index `interface`, or an
+    /// interface chosen by the system if it is 0.
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner
+            .get_ref()
+            .join_multicast_v6(*multiaddr, interface)
+    }
+
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner
+            .get_ref()
+            .leave_multicast_v6(*multiaddr, interface)
+    }
+
+    /// Set whether multicast packets sent from this socket are looped back to the local
+    /// host, enabled by default.
+    pub fn set_multicast_loop_v4(&self, multicast_loop: bool) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_loop_v4(multicast_loop)
+    }
+
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.inner.get_ref().multicast_loop_v4()
+    }
+
+    pub fn set_multicast_loop_v6(&self, multicast_loop: bool) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_loop_v6(multicast_loop)
+    }
+
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.inner.get_ref().multicast_loop_v6()
+    }
+
+    /// Set the time to live of sent IPv4 multicast packets, 1 by default to keep them on the
+    /// local network.
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_ttl_v4(ttl)
+    }
+
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        self.inner.get_ref().multicast_ttl_v4()
+    }
+
+    /// Send multicast packets on the interface of index `interface`, or an interface chosen
+    /// by the routing table if it is 0.
+    pub fn set_multicast_if(&self, interface: u32) -> io::Result<()> {
+        self.inner.get_ref().set_multicast_if(interface)
+    }
+
+    /// Enable UDP generic receive offload with `UDP_GRO`: the kernel may then coalesce
+    /// datagrams of the same size from the same source, to be received at once with
+    /// [`recv_from_gro`](UdpSocket::recv_from_gro).
+    pub fn set_gro(&self, gro: bool) -> io::Result<()> {
+        self.inner.get_ref().set_gro(gro)
+    }
+
+    pub fn gro(&self) -> io::Result<bool> {
+        self.inner.get_ref().gro()
+    }
+
+    /// Receive the destination address of datagrams and the interface they arrived on, with
+    /// `IP_PKTINFO` or `IPV6_RECVPKTINFO`, see [`recv_msg`](UdpSocket::recv_msg).
+    pub fn set_recv_pktinfo(&self, on: bool) -> io::Result<()> {
+        self.inner.get_ref().set_recv_pktinfo(on)
+    }
+
+    pub fn recv_pktinfo(&self) -> io::Result<bool> {
+        self.inner.get_ref().recv_pktinfo()
+    }
+
+    /// Receive the time the kernel received datagrams at, with `SO_TIMESTAMPNS`.
+    pub fn set_recv_timestamps(&self, on: bool) -> io::Result<()> {
+        self.inner.get_ref().set_recv_timestamps(on)
+    }
+
+    pub fn recv_timestamps(&self) -> io::Result<bool> {
+        self.inner.get_ref().recv_timestamps()
+    }
+
+    /// Receive the TOS byte or traffic class of datagrams, ECN bits included, with
+    /// `IP_RECVTOS` or `IPV6_RECVTCLASS`.
+    pub fn set_recv_tos(&self, on: bool) -> io::Result<()> {
+        self.inner.get_ref().set_recv_tos(on)
+    }
+
+    pub fn recv_tos(&self) -> io::Result<bool> {
+        self.inner.get_ref().recv_tos()
+    }
+
+    /// Receive the TTL or hop limit of datagrams, with `IP_RECVTTL` or `IPV6_RECVHOPLIMIT`.
+    pub fn set_recv_ttl(&self, on: bool) -> io::Result<()> {
+        self.inner.get_ref().set_recv_ttl(on)
+    }
+
+    pub fn recv_ttl(&self) -> io::Result<bool> {
+        self.inner.get_ref().recv_ttl()
+    }
+
+    /// Get and clear the pending error of the socket, with `SO_ERROR`.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
+    }
+
+    /// Install the socket in the registered file table of the runtime, so that later
+    /// operations skip the per-operation file descriptor lookup in the kernel. The file
+    /// descriptor stays open for operations that need it.
+    pub fn register_fixed(&mut self) -> io::Result<()> {
+        self.inner.get_mut().register()
+    }
+
+    pub async fn connect<A: ToSocketAddrs>(&self, addr: A) -> io::Result<()> {
+        let addrs = addr::resolve(addr).await?;
+        let mut last_err = None;
+
+        for addr in addrs {
+            match self.connect_addr(addr).await {
+                Ok(_) => return Ok(()),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "could not resolve to any address",
+            )
+        }))
+    }
+
+    pub(crate) async fn connect_addr(&self, addr: SocketAddr) -> io::Result<()> {
+        poll_fn(|cx| self.inner.poll_connect(cx, &SockAddr::from(addr))).await
+    }
+
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| self.inner.poll_recv(cx, buf)).await
+    }
+
+    pub async fn recv2(&self, buf: &mut [u8]) -> io::Result<usize> {
+        poll_fn(|cx| self.inner.poll_recv2(cx, buf)).await
+    }
+
+    /// Receive a datagram and its source address with a multishot `recvmsg`, which keeps
+    /// receiving into buffers of the runtime's buffer ring between calls, without a
+    /// submission or an allocation per datagram.
+    ///
+    /// A datagram larger than the buffers of the ring fails with `InvalidData` instead of
+    /// being truncated, and the receive goes on. When the ring runs dry the call fails with
+    /// `ENOBUFS`, and the next one arms the receive again. Once it failed otherwise, later
+    /// calls return `ConnectionAborted`.
+    pub async fn recv_from_multi(&self) -> io::Result<(Buf, SocketAddr)> {
+        poll_fn(|cx| self.inner.poll_recv_from_multi(cx)).await
+    }
+
+    /// Return a stream of the received datagrams, each in a buffer of the runtime's buffer
+    /// ring, served by a single multishot receive like [`recv2`](UdpSocket::recv2), with
+    /// which it shares its state.
+    ///
+    /// Buffers go back to the ring once dropped. Holding on to many of them exhausts the
+    /// ring, which fails the receive with `ENOBUFS`, armed again on the next poll. The stream
+    /// ends after yielding any other error.
+    pub fn recv_stream(&self) -> RecvStream<'_> {
+        RecvStream {
+            socket: self,
+            done: false,
+        }
+    }
+
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        poll_fn(|cx| self.inner.poll_send(cx, buf)).await
+    }
+
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        poll_fn(|cx| self.inner.poll_recv_from(cx, buf)).await
+    }
+
+    pub async fn send_to<A: Into<SocketAddr>>(&self, buf: &[u8], target: A) -> io::Result<usize> {
+        let addr = target.into();
+        poll_fn(|cx| self.inner.poll_send_to(cx, buf, addr)).await
+    }
+
+    /// Send `buf` to `target` without copying it to the kernel, returning the number of bytes
+    /// sent along with the buffer. The buffer is only handed back once the kernel no longer
+    /// uses it.
+    pub async fn send_to_zc<A: Into<SocketAddr>>(
+        &self,
+        buf: Vec<u8>,
+        target: A,
+    ) -> io::Result<(usize, Vec<u8>)> {
+        Op::send_zc(self.inner.get_ref().target(), buf, Some(target.into()))?.await
+    }
+
+    /// Send a batch of datagrams, each buffer to its address, returning the result of each
+    /// send. The sends are submitted together, up to 128 at a time, and go out in order: the
+    /// first one to fail cancels the rest, whose results are then `ECANCELED`. If a batch
+    /// cannot be submitted, the error is the result of its first send.
+    pub async fn send_many(
+        &self,
+        bufs: &[(&[u8], SocketAddr)],
+    ) -> io::Result<Vec<io::Result<usize>>> {
+        let mut results = Vec::with_capacity(bufs.len());
+        for chunk in bufs.chunks(MAX_BATCH) {
+            match Op::send_many(self.inner.get_ref().target(), chunk) {
+                Ok(ops) => {
+                    for op in ops {
+                        results.push(op.await);
+                    }
+                }
+                Err(e) => results.push(Err(e)),
+            }
+            if results.iter().any(|res| res.is_err()) {
+                break;
+            }
+        }
+        while results.len() < bufs.len() {
+            results.push(Err(io::Error::from_raw_os_error(libc::ECANCELED)));
+        }
+        Ok(results)
+    }
+
+    /// Send `buf` to `target` as datagrams of `segment_size` bytes, the last one possibly
+    /// shorter, with UDP generic segmentation offload: the buffer is split by the kernel, or
+    /// the network card, so that one submission sends up to 64 datagrams. Returns the
+    /// number of bytes sent.
+    pub async fn send_to_segmented<A: Into<SocketAddr>>(
+        &self,
+        buf: &[u8],
+        segment_size: u16,
+        target: A,
+    ) -> io::Result<usize> {
+        let mut control = Cmsgs::with_capacity(cmsg::space(mem::size_of::<u16>()));
+        control.push(libc::SOL_UDP, libc::UDP_SEGMENT, segment_size);
+        let fd = self.inner.get_ref().target();
+        Op::sendmsg(fd, buf, target.into(), Some(control))?.await
+    }
+
+    /// Receive datagrams coalesced by UDP generic receive offload, see
+    /// [`set_gro`](UdpSocket::set_gro), returning the number of bytes read, the segment size
+    /// and the source address. `buf` then holds consecutive datagrams of the segment size,
+    /// the last one possibly shorter, it should be 64 KiB not to truncate them.
+    ///
+    /// Datagrams that were not coalesced are received alone, with their length as segment
+    /// size.
+    pub async fn recv_from_gro(&self, buf: &mut [u8]) -> io::Result<(usize, usize, SocketAddr)> {
+        let control = Cmsgs::with_capacity(cmsg::space(mem::size_of::<libc::c_int>()));
+        let fd = self.inner.get_ref().target();
+        let (data, addr, control) = Op::recvmsg(fd, buf.len(), Some(control))?.await?;
+        let n = data.len();
+        buf[..n].copy_from_slice(&data);
+        let segment_size = control
+            .and_then(|control| control.get::<libc::c_int>(libc::SOL_UDP, libc::UDP_GRO))
+            .map_or(n, |size| size as usize);
+        Ok((n, segment_size, addr))
+    }
+
+    /// Receive a datagram along with its ancillary data, returning the number of bytes read
+    /// and the metadata of the datagram. The socket options `set_recv_*` choose which parts
+    /// of the metadata are received.
+    pub async fn recv_msg(&self, buf: &mut [u8]) -> io::Result<(usize, RecvMeta)> {
+        let control = Cmsgs::with_capacity(RecvMeta::control_len());
+        let fd = self.inner.get_ref().target();
+        let (data, addr, control) = Op::recvmsg(fd, buf.len(), Some(control))?.await?;
+        let n = data.len();
+        buf[..n].copy_from_slice(&data);
+        Ok((n, RecvMeta::new(addr, control.as_ref())))
+    }
+
+    /// Send `buf` to `target` with the ancillary data of `meta`, returning the number of
+    /// bytes sent.
+    pub async fn send_msg<A: Into<SocketAddr>>(
+        &self,
+        buf: &[u8],
+        target: A,
+        meta: &SendMeta,
+    ) -> io::Result<usize> {
+        let target = target.into();
+        let control = meta.control(target)?;
+        let fd = self.inner.get_ref().target();
+        Op::sendmsg(fd, buf, target, control)?.await
+    }
+
+    pub fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.inner.poll_send(cx, buf)
+    }
+
+    pub fn poll_recv(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.inner.poll_recv(cx, buf)
+    }
+
+    pub fn poll_recv2(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        self.inner.poll_recv2(cx, buf)
+    }
+
+    pub fn poll_recv_from(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<(usize, SocketAddr)>> {
+        self.inner.poll_recv_from(cx, buf)
+    }
+
+    pub fn poll_recv_from_multi(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(Buf, SocketAddr)>> {
+        self.inner.poll_recv_from_multi(cx)
+    }
+
+    pub fn poll_send_to<A: Into<SocketAddr>>(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        target: A,
+    ) -> Poll<io::Result<usize>> {
+        let addr = target.into();
+        self.inner.poll_send_to(cx, buf, addr)
+    }
+}
+
+/// A stream of the datagrams received by a [`UdpSocket`], see [`UdpSocket::recv_stream`].
+pub struct RecvStream<'a> {
+    socket: &'a UdpSocket,
+    done: bool,
+}
+
+impl Stream for RecvStream<'_> {
+    type Item = io::Result<Buf>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        let res = ready!(self.socket.inner.poll_recv_buf(cx));
+        self.done = res.as_ref().is_err_and(|err| !packet::is_transient(err));
+        Poll::Ready(Some(res))
+    }
+}
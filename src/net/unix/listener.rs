@@ -3,10 +3,14 @@ use std::io;
 use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::unix::net;
 use std::path::Path;
+use std::pin::Pin;
 use std::task::{ready, Context, Poll};
 
+use futures_core::stream::Stream;
+
 use super::UnixStream;
-use crate::socket::{self, socketaddr::SocketAddr, Socket};
+use crate::socket::socketaddr::{self, SocketAddr};
+use crate::socket::{self, Socket};
 
 pub struct UnixListener {
     inner: socket::Listener,
@@ -30,11 +34,18 @@ impl UnixListener {
         poll_fn(|cx| self.poll_accept2(cx)).await
     }
 
+    /// Return a stream of the accepted connections, served by a single multishot accept
+    /// like [`accept2`](UnixListener::accept2), with which it shares its state.
+    ///
+    /// Like `std::net::Incoming`, the stream never ends: errors are yielded, and the next
+    /// poll arms a new multishot accept.
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming { listener: self }
+    }
+
     pub fn poll_accept2(&self, cx: &mut Context<'_>) -> Poll<io::Result<(UnixStream, SocketAddr)>> {
         let socket = ready!(self.inner.poll_accept2(cx))?;
-        let addr = SocketAddr::new(|sockaddr, socklen| {
-            syscall!(getsockname(socket.as_raw_fd(), sockaddr, socklen))
-        })?;
+        let addr = socketaddr::local(&socket)?;
         Poll::Ready(Ok((socket.into(), addr)))
     }
 
@@ -50,8 +61,22 @@ impl UnixListener {
     }
 
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        let socket = self.inner.as_raw_fd();
-        SocketAddr::new(|sockaddr, socklen| syscall!(getsockname(socket, sockaddr, socklen)))
+        socketaddr::local(&self.inner)
+    }
+}
+
+/// A stream of the connections accepted by a [`UnixListener`], see
+/// [`UnixListener::incoming`].
+pub struct Incoming<'a> {
+    listener: &'a UnixListener,
+}
+
+impl Stream for Incoming<'_> {
+    type Item = io::Result<UnixStream>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let res = ready!(self.listener.inner.poll_accept2(cx));
+        Poll::Ready(Some(res.map(UnixStream::from)))
     }
 }
 
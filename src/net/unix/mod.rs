@@ -1,5 +1,7 @@
 mod listener;
+mod split;
 mod stream;
 
-pub use listener::UnixListener;
+pub use listener::{Incoming, UnixListener};
+pub use split::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, ReuniteError, WriteHalf};
 pub use stream::UnixStream;
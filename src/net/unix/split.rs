@@ -0,0 +1,5 @@
+use super::UnixStream;
+use crate::socket::socketaddr::{self, SocketAddr};
+use crate::socket::stream::stream_halves;
+
+stream_halves!(UnixStream, SocketAddr, socketaddr::local, socketaddr::peer);
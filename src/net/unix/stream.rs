@@ -1,6 +1,6 @@
 use std::future::poll_fn;
-use std::io;
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::io::{self, IoSlice, IoSliceMut};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::os::unix::net;
 use std::path::Path;
 use std::pin::Pin;
@@ -9,7 +9,10 @@ use std::task::{Context, Poll};
 use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
 use socket2::SockAddr;
 
-use crate::socket::{self, socketaddr::SocketAddr, Socket};
+use super::split::{self, OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use crate::fs::File;
+use crate::socket::socketaddr::{self, SocketAddr};
+use crate::socket::{self, Socket};
 
 pub struct UnixStream {
     inner: socket::Stream,
@@ -28,20 +31,79 @@ impl UnixStream {
     }
 
     pub fn from_std(stream: net::UnixStream) -> io::Result<UnixStream> {
-        let socket = unsafe { Socket::from_raw_fd(stream.as_raw_fd()) };
+        let socket = unsafe { Socket::from_raw_fd(stream.into_raw_fd()) };
         Ok(UnixStream {
             inner: socket::Stream::new(socket),
         })
     }
 
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        let fd = self.inner.get_ref().as_raw_fd();
-        SocketAddr::new(|sockaddr, socklen| syscall!(getsockname(fd, sockaddr, socklen)))
+        socketaddr::local(self.inner.get_ref())
+    }
+
+    /// Set the size of the send buffer with `SO_SNDBUF`, the kernel doubles it.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.get_ref().set_send_buffer_size(size)
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.get_ref().send_buffer_size()
+    }
+
+    /// Set the size of the receive buffer with `SO_RCVBUF`, the kernel doubles it.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.get_ref().set_recv_buffer_size(size)
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.get_ref().recv_buffer_size()
+    }
+
+    /// Get and clear the pending error of the socket, with `SO_ERROR`.
+    pub fn take_error(&self) -> io::Result<Option<io::Error>> {
+        self.inner.get_ref().take_error()
     }
 
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
-        let fd = self.inner.get_ref().as_raw_fd();
-        SocketAddr::new(|sockaddr, socklen| syscall!(getpeername(fd, sockaddr, socklen)))
+        socketaddr::peer(self.inner.get_ref())
+    }
+
+    /// Split the stream into a read half and a write half borrowing it, which can be used
+    /// concurrently, for example in a `join` of a read loop and a write loop.
+    ///
+    /// With `AsyncReadExt` from the futures crate in scope, `stream.split()` resolves to its
+    /// by-value `split`, call `UnixStream::split(&mut stream)` instead.
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split::split(&mut self.inner)
+    }
+
+    /// Split the stream into owned read and write halves, which can be moved to different
+    /// tasks. The halves can be put back together with `reunite`.
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split::into_split(self.inner)
+    }
+
+    /// Buffer writes smaller than `capacity` bytes, and send them together once the buffer
+    /// fills up or the stream is flushed, like `TCP_CORK` does in the kernel. Larger writes
+    /// are sent directly once the buffer is empty. A capacity of 0, the default, sends each
+    /// write on its own.
+    ///
+    /// Buffered bytes are lost if the stream is dropped without being flushed or closed.
+    pub fn set_write_buffer(&mut self, capacity: usize) {
+        self.inner.set_write_buffer(capacity)
+    }
+
+    pub(super) fn from_inner(inner: socket::Stream) -> UnixStream {
+        UnixStream { inner }
+    }
+
+    /// Send `len` bytes of `file` starting at `offset` without copying them through
+    /// userspace, returning the number of bytes sent. Fewer bytes are sent if the file ends
+    /// first, or if an error occurs after some bytes were sent. The file cursor is not
+    /// affected. Buffered writes are flushed first.
+    pub async fn send_file(&mut self, file: &File, offset: u64, len: u64) -> io::Result<u64> {
+        poll_fn(|cx| self.inner.poll_flush(cx)).await?;
+        crate::io::send_file(self.inner.get_ref().target(), file, offset, len).await
     }
 }
 
@@ -53,6 +115,14 @@ impl AsyncRead for UnixStream {
     ) -> Poll<io::Result<usize>> {
         self.get_mut().inner.poll_read(cx, buf)
     }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().inner.poll_read_vectored(cx, bufs)
+    }
 }
 
 impl AsyncBufRead for UnixStream {
@@ -70,8 +140,16 @@ impl AsyncWrite for UnixStream {
         self.get_mut().inner.poll_write(cx, buf)
     }
 
-    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
-        Poll::Ready(Ok(()))
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().inner.poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        self.get_mut().inner.poll_flush(cx)
     }
 
     fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
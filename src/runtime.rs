@@ -4,20 +4,48 @@ use std::io;
 use std::pin::pin;
 use std::task::{Context, Poll};
 
+use crate::buffer::HugePages;
 use crate::driver::Driver;
 use crate::local_executor;
 use crate::waker_fn::waker_fn;
 
-pub(crate) struct Runtime {
+/// A runtime driving futures on the current thread, [`block_on`](crate::block_on) uses one
+/// with the default configuration.
+pub struct Runtime {
     driver: Driver,
 }
 
-impl Runtime {
-    pub fn new() -> io::Result<Runtime> {
+/// Configure and build a [`Runtime`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Builder {
+    huge_pages: HugePages,
+}
+
+impl Builder {
+    /// Create a builder with the default configuration, buffers backed by regular pages.
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// Back the buffers the kernel receives into with huge pages, which reduces the TLB
+    /// pressure of large buffer rings.
+    pub fn huge_pages(mut self, huge_pages: HugePages) -> Builder {
+        self.huge_pages = huge_pages;
+        self
+    }
+
+    /// Build a runtime with this configuration, failing if its io_uring cannot be set up.
+    pub fn build(&self) -> io::Result<Runtime> {
         Ok(Runtime {
-            driver: Driver::new()?,
+            driver: Driver::new(self.huge_pages)?,
         })
     }
+}
+
+impl Runtime {
+    pub fn new() -> io::Result<Runtime> {
+        Builder::new().build()
+    }
 
     pub fn block_on<F>(&self, future: F) -> F::Output
     where
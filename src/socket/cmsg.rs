@@ -0,0 +1,89 @@
+use std::mem;
+use std::ptr;
+
+// A buffer of control messages, the ancillary data of `sendmsg` and `recvmsg`, aligned for
+// `cmsghdr` as the kernel requires.
+pub(crate) struct Cmsgs {
+    buf: Vec<u64>,
+    len: usize,
+}
+
+// The space a control message with a payload of `len` bytes takes, padding included.
+pub(crate) fn space(len: usize) -> usize {
+    unsafe { libc::CMSG_SPACE(len as u32) as usize }
+}
+
+impl Cmsgs {
+    // An empty buffer with room for `capacity` bytes of control messages.
+    pub(crate) fn with_capacity(capacity: usize) -> Cmsgs {
+        Cmsgs {
+            buf: vec![0; capacity.div_ceil(mem::size_of::<u64>())],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.buf.len() * mem::size_of::<u64>()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut libc::c_void {
+        self.buf.as_mut_ptr().cast()
+    }
+
+    // Set the length of the control messages the kernel wrote, `msg_controllen` after a
+    // `recvmsg`.
+    pub(crate) unsafe fn set_len(&mut self, len: usize) {
+        self.len = len.min(self.capacity());
+    }
+
+    // Append a control message of `level` and `ty` carrying `value`.
+    //
+    // Panics if the buffer is too small, callers size it with `space`.
+    pub(crate) fn push<T: Copy>(&mut self, level: libc::c_int, ty: libc::c_int, value: T) {
+        let size = mem::size_of::<T>();
+        assert!(self.len + space(size) <= self.capacity());
+        unsafe {
+            let hdr = self.as_mut_ptr().cast::<u8>().add(self.len);
+            let cmsg = libc::cmsghdr {
+                cmsg_len: libc::CMSG_LEN(size as u32) as _,
+                cmsg_level: level,
+                cmsg_type: ty,
+            };
+            ptr::write_unaligned(hdr.cast(), cmsg);
+            let data = libc::CMSG_DATA(hdr.cast());
+            ptr::write_unaligned(data.cast(), value);
+        }
+        self.len += space(size);
+    }
+
+    // Iterate over the level, type and payload of the control messages.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (libc::c_int, libc::c_int, &[u8])> {
+        let bytes = unsafe { std::slice::from_raw_parts(self.buf.as_ptr().cast::<u8>(), self.len) };
+        let header_len = unsafe { libc::CMSG_LEN(0) as usize };
+        let mut pos = 0;
+        std::iter::from_fn(move || {
+            let rest = bytes.get(pos..)?;
+            if rest.len() < header_len {
+                return None;
+            }
+            let cmsg = unsafe { ptr::read_unaligned(rest.as_ptr().cast::<libc::cmsghdr>()) };
+            let len = cmsg.cmsg_len as usize;
+            if len < header_len || len > rest.len() {
+                return None;
+            }
+            pos += space(len - header_len);
+            Some((cmsg.cmsg_level, cmsg.cmsg_type, &rest[header_len..len]))
+        })
+    }
+
+    // The payload of the first control message of `level` and `ty`, read as a `T`.
+    pub(crate) fn get<T: Copy>(&self, level: libc::c_int, ty: libc::c_int) -> Option<T> {
+        self.iter()
+            .find(|&(l, t, data)| l == level && t == ty && data.len() >= mem::size_of::<T>())
+            .map(|(_, _, data)| unsafe { ptr::read_unaligned(data.as_ptr().cast()) })
+    }
+}
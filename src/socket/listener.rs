@@ -8,7 +8,7 @@ use std::pin::Pin;
 use std::task::{ready, Context, Poll};
 
 use super::{Socket, SocketStorage};
-use crate::driver::{self, Op};
+use crate::driver::{self, Fd, Op};
 
 pub(crate) struct Listener {
     inner: RefCell<Inner>,
@@ -21,6 +21,7 @@ impl Listener {
             io,
             inner: RefCell::new(Inner {
                 accept: AcceptState::Idle,
+                accept_direct: AcceptState::Idle,
                 accept_multi: AcceptMultiState::Idle,
             }),
         }
@@ -30,6 +31,10 @@ impl Listener {
         &self.io
     }
 
+    pub(crate) fn get_mut(&mut self) -> &mut Socket {
+        &mut self.io
+    }
+
     pub(crate) fn bind(addr: SocketAddr) -> io::Result<Listener> {
         let socket = Socket::bind(addr, libc::SOCK_STREAM)?;
         socket.listen(1024)?;
@@ -49,13 +54,22 @@ impl Listener {
         &self,
         cx: &mut Context<'_>,
     ) -> Poll<io::Result<(Socket, SocketStorage)>> {
-        self.inner.borrow_mut().poll_accept(cx, self.io.as_raw_fd())
+        self.inner
+            .borrow_mut()
+            .poll_accept(cx, self.io.target(), false)
     }
 
-    pub(crate) fn poll_accept2(&self, cx: &mut Context<'_>) -> Poll<io::Result<Socket>> {
+    pub(crate) fn poll_accept_direct(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<(Socket, SocketStorage)>> {
         self.inner
             .borrow_mut()
-            .poll_accept2(cx, self.io.as_raw_fd())
+            .poll_accept(cx, self.io.target(), true)
+    }
+
+    pub(crate) fn poll_accept2(&self, cx: &mut Context<'_>) -> Poll<io::Result<Socket>> {
+        self.inner.borrow_mut().poll_accept2(cx, self.io.target())
     }
 
     pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
@@ -65,7 +79,7 @@ impl Listener {
 
 impl FromRawFd for Listener {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
-        Listener::new(Socket { fd })
+        Listener::new(Socket::from(fd))
     }
 }
 
@@ -77,6 +91,7 @@ impl AsRawFd for Listener {
 
 struct Inner {
     accept: AcceptState,
+    accept_direct: AcceptState,
     accept_multi: AcceptMultiState,
 }
 
@@ -88,23 +103,29 @@ enum AcceptState {
 enum AcceptMultiState {
     Idle,
     Accepting(Op<driver::AcceptMulti>),
-    Done,
 }
 
 impl Inner {
     pub fn poll_accept(
         &mut self,
         cx: &mut Context<'_>,
-        fd: RawFd,
+        fd: Fd,
+        direct: bool,
     ) -> Poll<io::Result<(Socket, SocketStorage)>> {
+        let state = if direct {
+            &mut self.accept_direct
+        } else {
+            &mut self.accept
+        };
         loop {
-            match &mut self.accept {
+            match state {
                 AcceptState::Idle => {
-                    self.accept = AcceptState::Accepting(Op::accept(fd)?);
+                    *state = AcceptState::Accepting(Op::accept(fd, direct)?);
                 }
                 AcceptState::Accepting(op) => {
-                    let (socket, socketaddr) = ready!(Pin::new(op).poll(cx))?;
-                    self.accept = AcceptState::Idle;
+                    let res = ready!(Pin::new(op).poll(cx));
+                    *state = AcceptState::Idle;
+                    let (socket, socketaddr) = res?;
                     return Poll::Ready(Ok((
                         socket,
                         SocketStorage {
@@ -117,7 +138,9 @@ impl Inner {
         }
     }
 
-    pub fn poll_accept2(&mut self, cx: &mut Context<'_>, fd: RawFd) -> Poll<io::Result<Socket>> {
+    // Accept a connection with the multishot accept, which is armed again by the next call
+    // once it failed.
+    pub fn poll_accept2(&mut self, cx: &mut Context<'_>, fd: Fd) -> Poll<io::Result<Socket>> {
         loop {
             match &mut self.accept_multi {
                 AcceptMultiState::Idle => {
@@ -125,25 +148,20 @@ impl Inner {
                 }
                 AcceptMultiState::Accepting(op) => {
                     if let Some(res) = op.get_mut().next() {
-                        let fd = res.result.map(|fd| fd as i32).map_err(|err| {
-                            self.accept_multi = AcceptMultiState::Done;
-                            err
+                        let fd = res.result.map(|fd| fd as i32).inspect_err(|_| {
+                            self.accept_multi = AcceptMultiState::Idle;
                         })?;
                         let socket = unsafe { Socket::from_raw_fd(fd) };
                         return Poll::Ready(Ok(socket));
                     }
                     let res = ready!(Pin::new(op).poll(cx));
-                    let fd = res.result.map(|fd| fd as i32).map_err(|err| {
-                        self.accept_multi = AcceptMultiState::Done;
-                        err
+                    let fd = res.result.map(|fd| fd as i32).inspect_err(|_| {
+                        self.accept_multi = AcceptMultiState::Idle;
                     })?;
                     let socket = unsafe { Socket::from_raw_fd(fd) };
                     self.accept_multi = AcceptMultiState::Idle;
                     return Poll::Ready(Ok(socket));
                 }
-                AcceptMultiState::Done => {
-                    return Poll::Ready(Err(io::ErrorKind::ConnectionAborted.into()));
-                }
             }
         }
     }
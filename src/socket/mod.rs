@@ -1,11 +1,14 @@
+pub(crate) mod cmsg;
 pub(crate) mod listener;
+mod options;
 pub(crate) mod packet;
 pub(crate) mod socketaddr;
 pub(crate) mod stream;
 
 pub(crate) use listener::Listener;
+pub(crate) use options::TcpInfoRaw;
 pub(crate) use packet::Packet;
-pub(crate) use stream::Stream;
+pub(crate) use stream::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, Stream, WriteHalf};
 
 use std::io;
 use std::mem;
@@ -15,6 +18,8 @@ use std::path::Path;
 
 use socket2::SockAddr;
 
+use crate::driver::{self, Fd};
+
 pub(crate) struct SocketStorage {
     pub(crate) storage: libc::sockaddr_storage,
     pub(crate) socklen: libc::socklen_t,
@@ -22,6 +27,9 @@ pub(crate) struct SocketStorage {
 
 pub(crate) struct Socket {
     fd: RawFd,
+    // The slot of the socket in the registered file table, operations use it instead of `fd`
+    // once set. Sockets accepted as direct descriptors only have a slot, and `fd` is -1.
+    fixed: Option<u32>,
 }
 
 fn get_domain(socket_addr: SocketAddr) -> libc::c_int {
@@ -36,14 +44,14 @@ impl Socket {
         let socket_type = socket_type | libc::SOCK_CLOEXEC;
         let domain = get_domain(socket_addr);
         let fd = socket2::Socket::new(domain.into(), socket_type.into(), None)?.into_raw_fd();
-        Ok(Socket { fd })
+        Ok(Socket::from(fd))
     }
 
     pub(crate) fn new_unix(socket_type: libc::c_int) -> io::Result<Socket> {
         let socket_type = socket_type | libc::SOCK_CLOEXEC;
         let domain = libc::AF_UNIX;
         let fd = socket2::Socket::new(domain.into(), socket_type.into(), None)?.into_raw_fd();
-        Ok(Socket { fd })
+        Ok(Socket::from(fd))
     }
 
     pub(crate) fn bind(socket_addr: SocketAddr, socket_type: libc::c_int) -> io::Result<Socket> {
@@ -68,33 +76,65 @@ impl Socket {
         socket_type: socket2::Type,
     ) -> io::Result<Socket> {
         let sys_listener = socket2::Socket::new(domain, socket_type, None)?;
-        sys_listener.set_reuse_port(true)?;
-        sys_listener.set_reuse_address(true)?;
+        // Like std, listeners can bind while connections of a previous one linger in
+        // `TIME_WAIT`. Sharing the port with `SO_REUSEPORT` is left to `TcpSocket`.
+        if socket_type == socket2::Type::STREAM {
+            sys_listener.set_reuse_address(true)?;
+        }
         sys_listener.bind(&socket_addr)?;
         let fd = sys_listener.into_raw_fd();
-        Ok(Self { fd })
+        Ok(Socket::from(fd))
+    }
+
+    pub(crate) fn from_fixed(index: u32) -> Socket {
+        Socket {
+            fd: -1,
+            fixed: Some(index),
+        }
+    }
+
+    // The file descriptor for system calls on the socket. Sockets accepted as direct
+    // descriptors have none, system calls on them are not supported.
+    pub(crate) fn fd(&self) -> io::Result<RawFd> {
+        if self.fd < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "socket accepted as a direct descriptor has no file descriptor",
+            ));
+        }
+        Ok(self.fd)
+    }
+
+    // The target of io_uring operations on the socket.
+    pub(crate) fn target(&self) -> Fd {
+        match self.fixed {
+            Some(index) => Fd::Fixed(index),
+            None => Fd::Raw(self.fd),
+        }
+    }
+
+    // Install the socket in the registered file table, so that operations skip the file
+    // descriptor lookup.
+    pub(crate) fn register(&mut self) -> io::Result<()> {
+        if self.fixed.is_none() {
+            self.fixed = Some(driver::register_file(self.fd)?);
+        }
+        Ok(())
     }
 
     pub(crate) fn listen(&self, backlog: libc::c_int) -> io::Result<()> {
-        syscall!(listen(self.as_raw_fd(), backlog))?;
+        syscall!(listen(self.fd()?, backlog))?;
         Ok(())
     }
 
     pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
-        sockname(|buf, len| syscall!(getsockname(self.as_raw_fd(), buf, len)))
+        let fd = self.fd()?;
+        sockname(|buf, len| syscall!(getsockname(fd, buf, len)))
     }
 
     pub(crate) fn peer_addr(&self) -> io::Result<SocketAddr> {
-        sockname(|buf, len| syscall!(getpeername(self.as_raw_fd(), buf, len)))
-    }
-
-    pub(crate) fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
-        setsockopt(
-            self.as_raw_fd(),
-            libc::IPPROTO_TCP,
-            libc::TCP_NODELAY,
-            nodelay as libc::c_int,
-        )
+        let fd = self.fd()?;
+        sockname(|buf, len| syscall!(getpeername(fd, buf, len)))
     }
 }
 
@@ -115,6 +155,19 @@ fn setsockopt<T>(
     Ok(())
 }
 
+fn getsockopt<T: Copy>(sock: libc::c_int, opt: libc::c_int, val: libc::c_int) -> io::Result<T> {
+    let mut payload: T = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<T>() as libc::socklen_t;
+    syscall!(getsockopt(
+        sock,
+        opt,
+        val,
+        &mut payload as *mut T as *mut libc::c_void,
+        &mut len,
+    ))?;
+    Ok(payload)
+}
+
 pub(crate) fn sockname<F>(f: F) -> io::Result<SocketAddr>
 where
     F: FnOnce(*mut libc::sockaddr, *mut libc::socklen_t) -> io::Result<libc::c_int>,
@@ -135,7 +188,12 @@ where
 
 impl Drop for Socket {
     fn drop(&mut self) {
-        let _ = unsafe { libc::close(self.fd) };
+        if let Some(index) = self.fixed {
+            driver::unregister_file(index);
+        }
+        if self.fd >= 0 {
+            let _ = unsafe { libc::close(self.fd) };
+        }
     }
 }
 
@@ -147,12 +205,12 @@ impl AsRawFd for Socket {
 
 impl From<RawFd> for Socket {
     fn from(fd: RawFd) -> Self {
-        Socket { fd }
+        Socket { fd, fixed: None }
     }
 }
 
 impl FromRawFd for Socket {
     unsafe fn from_raw_fd(fd: RawFd) -> Self {
-        Socket { fd }
+        Socket::from(fd)
     }
 }
@@ -0,0 +1,447 @@
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
+use super::{getsockopt, setsockopt, Socket};
+
+// The options enabling the control messages of received datagrams, for IPv4 and IPv6 sockets.
+const RECV_PKTINFO_V4: (libc::c_int, libc::c_int) = (libc::IPPROTO_IP, libc::IP_PKTINFO);
+const RECV_PKTINFO_V6: (libc::c_int, libc::c_int) = (libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO);
+const RECV_TOS_V4: (libc::c_int, libc::c_int) = (libc::IPPROTO_IP, libc::IP_RECVTOS);
+const RECV_TOS_V6: (libc::c_int, libc::c_int) = (libc::IPPROTO_IPV6, libc::IPV6_RECVTCLASS);
+const RECV_TTL_V4: (libc::c_int, libc::c_int) = (libc::IPPROTO_IP, libc::IP_RECVTTL);
+const RECV_TTL_V6: (libc::c_int, libc::c_int) = (libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT);
+
+// The longest congestion control algorithm name, including the nul byte.
+const TCP_CA_NAME_MAX: usize = 16;
+
+// The start of `struct tcp_info` from `linux/tcp.h`, up to `tcpi_delivery_rate` which was
+// added in Linux 4.9. The glibc definition in libc stops earlier. Older kernels fill in a
+// shorter prefix.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct TcpInfoRaw {
+    pub(crate) state: u8,
+    pub(crate) ca_state: u8,
+    pub(crate) retransmits: u8,
+    pub(crate) probes: u8,
+    pub(crate) backoff: u8,
+    pub(crate) options: u8,
+    pub(crate) wscale: u8,
+    pub(crate) app_limited: u8,
+    pub(crate) rto: u32,
+    pub(crate) ato: u32,
+    pub(crate) snd_mss: u32,
+    pub(crate) rcv_mss: u32,
+    pub(crate) unacked: u32,
+    pub(crate) sacked: u32,
+    pub(crate) lost: u32,
+    pub(crate) retrans: u32,
+    pub(crate) fackets: u32,
+    pub(crate) last_data_sent: u32,
+    pub(crate) last_ack_sent: u32,
+    pub(crate) last_data_recv: u32,
+    pub(crate) last_ack_recv: u32,
+    pub(crate) pmtu: u32,
+    pub(crate) rcv_ssthresh: u32,
+    pub(crate) rtt: u32,
+    pub(crate) rttvar: u32,
+    pub(crate) snd_ssthresh: u32,
+    pub(crate) snd_cwnd: u32,
+    pub(crate) advmss: u32,
+    pub(crate) reordering: u32,
+    pub(crate) rcv_rtt: u32,
+    pub(crate) rcv_space: u32,
+    pub(crate) total_retrans: u32,
+    pub(crate) pacing_rate: u64,
+    pub(crate) max_pacing_rate: u64,
+    pub(crate) bytes_acked: u64,
+    pub(crate) bytes_received: u64,
+    pub(crate) segs_out: u32,
+    pub(crate) segs_in: u32,
+    pub(crate) notsent_bytes: u32,
+    pub(crate) min_rtt: u32,
+    pub(crate) data_segs_in: u32,
+    pub(crate) data_segs_out: u32,
+    pub(crate) delivery_rate: u64,
+}
+
+impl Socket {
+    fn get<T: Copy>(&self, level: libc::c_int, name: libc::c_int) -> io::Result<T> {
+        getsockopt(self.fd()?, level, name)
+    }
+
+    fn set<T>(&self, level: libc::c_int, name: libc::c_int, value: T) -> io::Result<()> {
+        setsockopt(self.fd()?, level, name, value)
+    }
+
+    fn get_bool(&self, level: libc::c_int, name: libc::c_int) -> io::Result<bool> {
+        Ok(self.get::<libc::c_int>(level, name)? != 0)
+    }
+
+    fn set_bool(&self, level: libc::c_int, name: libc::c_int, value: bool) -> io::Result<()> {
+        self.set(level, name, value as libc::c_int)
+    }
+
+    fn is_ipv6(&self) -> io::Result<bool> {
+        Ok(self.get::<libc::c_int>(libc::SOL_SOCKET, libc::SO_DOMAIN)? == libc::AF_INET6)
+    }
+
+    pub(crate) fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.set_bool(libc::IPPROTO_TCP, libc::TCP_NODELAY, nodelay)
+    }
+
+    pub(crate) fn nodelay(&self) -> io::Result<bool> {
+        self.get_bool(libc::IPPROTO_TCP, libc::TCP_NODELAY)
+    }
+
+    pub(crate) fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.set_bool(libc::SOL_SOCKET, libc::SO_KEEPALIVE, keepalive)
+    }
+
+    pub(crate) fn keepalive(&self) -> io::Result<bool> {
+        self.get_bool(libc::SOL_SOCKET, libc::SO_KEEPALIVE)
+    }
+
+    pub(crate) fn set_keepalive_idle(&self, idle: Duration) -> io::Result<()> {
+        self.set(libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, secs(idle))
+    }
+
+    pub(crate) fn keepalive_idle(&self) -> io::Result<Duration> {
+        let idle: libc::c_int = self.get(libc::IPPROTO_TCP, libc::TCP_KEEPIDLE)?;
+        Ok(Duration::from_secs(idle as u64))
+    }
+
+    pub(crate) fn set_keepalive_interval(&self, interval: Duration) -> io::Result<()> {
+        self.set(libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, secs(interval))
+    }
+
+    pub(crate) fn keepalive_interval(&self) -> io::Result<Duration> {
+        let interval: libc::c_int = self.get(libc::IPPROTO_TCP, libc::TCP_KEEPINTVL)?;
+        Ok(Duration::from_secs(interval as u64))
+    }
+
+    pub(crate) fn set_keepalive_retries(&self, retries: u32) -> io::Result<()> {
+        let retries = retries.min(libc::c_int::MAX as u32) as libc::c_int;
+        self.set(libc::IPPROTO_TCP, libc::TCP_KEEPCNT, retries)
+    }
+
+    pub(crate) fn keepalive_retries(&self) -> io::Result<u32> {
+        let retries: libc::c_int = self.get(libc::IPPROTO_TCP, libc::TCP_KEEPCNT)?;
+        Ok(retries as u32)
+    }
+
+    pub(crate) fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        let linger = libc::linger {
+            l_onoff: linger.is_some() as libc::c_int,
+            l_linger: linger.map_or(0, secs),
+        };
+        self.set(libc::SOL_SOCKET, libc::SO_LINGER, linger)
+    }
+
+    pub(crate) fn linger(&self) -> io::Result<Option<Duration>> {
+        let linger: libc::linger = self.get(libc::SOL_SOCKET, libc::SO_LINGER)?;
+        Ok((linger.l_onoff != 0).then(|| Duration::from_secs(linger.l_linger as u64)))
+    }
+
+    // `IP_TTL` for IPv4 sockets, `IPV6_UNICAST_HOPS` for IPv6 sockets.
+    pub(crate) fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        let ttl = ttl.min(libc::c_int::MAX as u32) as libc::c_int;
+        if self.is_ipv6()? {
+            self.set(libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS, ttl)
+        } else {
+            self.set(libc::IPPROTO_IP, libc::IP_TTL, ttl)
+        }
+    }
+
+    pub(crate) fn ttl(&self) -> io::Result<u32> {
+        let ttl: libc::c_int = if self.is_ipv6()? {
+            self.get(libc::IPPROTO_IPV6, libc::IPV6_UNICAST_HOPS)?
+        } else {
+            self.get(libc::IPPROTO_IP, libc::IP_TTL)?
+        };
+        Ok(ttl as u32)
+    }
+
+    pub(crate) fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.set_bool(libc::IPPROTO_IPV6, libc::IPV6_V6ONLY, only_v6)
+    }
+
+    pub(crate) fn only_v6(&self) -> io::Result<bool> {
+        self.get_bool(libc::IPPROTO_IPV6, libc::IPV6_V6ONLY)
+    }
+
+    pub(crate) fn set_user_timeout(&self, timeout: Duration) -> io::Result<()> {
+        let timeout = timeout.as_millis().min(libc::c_uint::MAX as u128) as libc::c_uint;
+        self.set(libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT, timeout)
+    }
+
+    pub(crate) fn user_timeout(&self) -> io::Result<Duration> {
+        let timeout: libc::c_uint = self.get(libc::IPPROTO_TCP, libc::TCP_USER_TIMEOUT)?;
+        Ok(Duration::from_millis(timeout as u64))
+    }
+
+    pub(crate) fn set_quickack(&self, quickack: bool) -> io::Result<()> {
+        self.set_bool(libc::IPPROTO_TCP, libc::TCP_QUICKACK, quickack)
+    }
+
+    pub(crate) fn quickack(&self) -> io::Result<bool> {
+        self.get_bool(libc::IPPROTO_TCP, libc::TCP_QUICKACK)
+    }
+
+    pub(crate) fn set_notsent_lowat(&self, lowat: u32) -> io::Result<()> {
+        self.set(libc::IPPROTO_TCP, libc::TCP_NOTSENT_LOWAT, lowat)
+    }
+
+    pub(crate) fn notsent_lowat(&self) -> io::Result<u32> {
+        self.get(libc::IPPROTO_TCP, libc::TCP_NOTSENT_LOWAT)
+    }
+
+    pub(crate) fn set_congestion(&self, algorithm: &str) -> io::Result<()> {
+        syscall!(setsockopt(
+            self.fd()?,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONGESTION,
+            algorithm.as_ptr() as *const libc::c_void,
+            algorithm.len() as libc::socklen_t,
+        ))?;
+        Ok(())
+    }
+
+    pub(crate) fn congestion(&self) -> io::Result<String> {
+        let mut name = [0u8; TCP_CA_NAME_MAX];
+        let mut len = name.len() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.fd()?,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONGESTION,
+            name.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+        ))?;
+        let name = name[..len as usize].split(|&b| b == 0).next().unwrap();
+        Ok(String::from_utf8_lossy(name).into_owned())
+    }
+
+    pub(crate) fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+        self.set(libc::SOL_SOCKET, libc::SO_SNDBUF, size)
+    }
+
+    pub(crate) fn send_buffer_size(&self) -> io::Result<usize> {
+        let size: libc::c_int = self.get(libc::SOL_SOCKET, libc::SO_SNDBUF)?;
+        Ok(size as usize)
+    }
+
+    pub(crate) fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        let size = size.min(libc::c_int::MAX as usize) as libc::c_int;
+        self.set(libc::SOL_SOCKET, libc::SO_RCVBUF, size)
+    }
+
+    pub(crate) fn recv_buffer_size(&self) -> io::Result<usize> {
+        let size: libc::c_int = self.get(libc::SOL_SOCKET, libc::SO_RCVBUF)?;
+        Ok(size as usize)
+    }
+
+    // Returns the statistics along with the number of bytes of them filled in by the kernel,
+    // the fields past it are zero.
+    pub(crate) fn tcp_info(&self) -> io::Result<(TcpInfoRaw, usize)> {
+        let mut info: TcpInfoRaw = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<TcpInfoRaw>() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.fd()?,
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut TcpInfoRaw as *mut libc::c_void,
+            &mut len,
+        ))?;
+        Ok((info, len as usize))
+    }
+
+    pub(crate) fn set_broadcast(&self, broadcast: bool) -> io::Result<()> {
+        self.set_bool(libc::SOL_SOCKET, libc::SO_BROADCAST, broadcast)
+    }
+
+    pub(crate) fn broadcast(&self) -> io::Result<bool> {
+        self.get_bool(libc::SOL_SOCKET, libc::SO_BROADCAST)
+    }
+
+    pub(crate) fn join_multicast_v4(
+        &self,
+        multiaddr: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> io::Result<()> {
+        self.set(
+            libc::IPPROTO_IP,
+            libc::IP_ADD_MEMBERSHIP,
+            ip_mreq(multiaddr, interface),
+        )
+    }
+
+    pub(crate) fn leave_multicast_v4(
+        &self,
+        multiaddr: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> io::Result<()> {
+        self.set(
+            libc::IPPROTO_IP,
+            libc::IP_DROP_MEMBERSHIP,
+            ip_mreq(multiaddr, interface),
+        )
+    }
+
+    pub(crate) fn join_multicast_v6(&self, multiaddr: Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.set(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_ADD_MEMBERSHIP,
+            ipv6_mreq(multiaddr, interface),
+        )
+    }
+
+    pub(crate) fn leave_multicast_v6(&self, multiaddr: Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.set(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_DROP_MEMBERSHIP,
+            ipv6_mreq(multiaddr, interface),
+        )
+    }
+
+    pub(crate) fn set_multicast_loop_v4(&self, multicast_loop: bool) -> io::Result<()> {
+        self.set_bool(libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP, multicast_loop)
+    }
+
+    pub(crate) fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.get_bool(libc::IPPROTO_IP, libc::IP_MULTICAST_LOOP)
+    }
+
+    pub(crate) fn set_multicast_loop_v6(&self, multicast_loop: bool) -> io::Result<()> {
+        self.set_bool(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MULTICAST_LOOP,
+            multicast_loop,
+        )
+    }
+
+    pub(crate) fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.get_bool(libc::IPPROTO_IPV6, libc::IPV6_MULTICAST_LOOP)
+    }
+
+    pub(crate) fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        let ttl = ttl.min(libc::c_int::MAX as u32) as libc::c_int;
+        self.set(libc::IPPROTO_IP, libc::IP_MULTICAST_TTL, ttl)
+    }
+
+    pub(crate) fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        Ok(self.get::<libc::c_int>(libc::IPPROTO_IP, libc::IP_MULTICAST_TTL)? as u32)
+    }
+
+    // `IP_MULTICAST_IF` with an interface index for IPv4 sockets, `IPV6_MULTICAST_IF` for IPv6
+    // sockets.
+    pub(crate) fn set_multicast_if(&self, interface: u32) -> io::Result<()> {
+        if self.is_ipv6()? {
+            self.set(
+                libc::IPPROTO_IPV6,
+                libc::IPV6_MULTICAST_IF,
+                interface as libc::c_int,
+            )
+        } else {
+            let mreqn = libc::ip_mreqn {
+                imr_multiaddr: libc::in_addr { s_addr: 0 },
+                imr_address: libc::in_addr { s_addr: 0 },
+                imr_ifindex: interface as libc::c_int,
+            };
+            self.set(libc::IPPROTO_IP, libc::IP_MULTICAST_IF, mreqn)
+        }
+    }
+
+    // Set the option of an IPv4 or IPv6 socket, `v4` or `v6`, to `on`.
+    fn set_bool_by_family(
+        &self,
+        v4: (libc::c_int, libc::c_int),
+        v6: (libc::c_int, libc::c_int),
+        on: bool,
+    ) -> io::Result<()> {
+        let (level, name) = if self.is_ipv6()? { v6 } else { v4 };
+        self.set_bool(level, name, on)
+    }
+
+    fn get_bool_by_family(
+        &self,
+        v4: (libc::c_int, libc::c_int),
+        v6: (libc::c_int, libc::c_int),
+    ) -> io::Result<bool> {
+        let (level, name) = if self.is_ipv6()? { v6 } else { v4 };
+        self.get_bool(level, name)
+    }
+
+    pub(crate) fn set_recv_pktinfo(&self, on: bool) -> io::Result<()> {
+        self.set_bool_by_family(RECV_PKTINFO_V4, RECV_PKTINFO_V6, on)
+    }
+
+    pub(crate) fn recv_pktinfo(&self) -> io::Result<bool> {
+        self.get_bool_by_family(RECV_PKTINFO_V4, RECV_PKTINFO_V6)
+    }
+
+    pub(crate) fn set_recv_tos(&self, on: bool) -> io::Result<()> {
+        self.set_bool_by_family(RECV_TOS_V4, RECV_TOS_V6, on)
+    }
+
+    pub(crate) fn recv_tos(&self) -> io::Result<bool> {
+        self.get_bool_by_family(RECV_TOS_V4, RECV_TOS_V6)
+    }
+
+    pub(crate) fn set_recv_ttl(&self, on: bool) -> io::Result<()> {
+        self.set_bool_by_family(RECV_TTL_V4, RECV_TTL_V6, on)
+    }
+
+    pub(crate) fn recv_ttl(&self) -> io::Result<bool> {
+        self.get_bool_by_family(RECV_TTL_V4, RECV_TTL_V6)
+    }
+
+    pub(crate) fn set_recv_timestamps(&self, on: bool) -> io::Result<()> {
+        self.set_bool(libc::SOL_SOCKET, libc::SO_TIMESTAMPNS, on)
+    }
+
+    pub(crate) fn recv_timestamps(&self) -> io::Result<bool> {
+        self.get_bool(libc::SOL_SOCKET, libc::SO_TIMESTAMPNS)
+    }
+
+    pub(crate) fn set_gro(&self, gro: bool) -> io::Result<()> {
+        self.set_bool(libc::SOL_UDP, libc::UDP_GRO, gro)
+    }
+
+    pub(crate) fn gro(&self) -> io::Result<bool> {
+        self.get_bool(libc::SOL_UDP, libc::UDP_GRO)
+    }
+
+    pub(crate) fn take_error(&self) -> io::Result<Option<io::Error>> {
+        let err: libc::c_int = self.get(libc::SOL_SOCKET, libc::SO_ERROR)?;
+        Ok((err != 0).then(|| io::Error::from_raw_os_error(err)))
+    }
+}
+
+fn in_addr(addr: Ipv4Addr) -> libc::in_addr {
+    libc::in_addr {
+        s_addr: u32::from_ne_bytes(addr.octets()),
+    }
+}
+
+fn ip_mreq(multiaddr: Ipv4Addr, interface: Ipv4Addr) -> libc::ip_mreq {
+    libc::ip_mreq {
+        imr_multiaddr: in_addr(multiaddr),
+        imr_interface: in_addr(interface),
+    }
+}
+
+fn ipv6_mreq(multiaddr: Ipv6Addr, interface: u32) -> libc::ipv6_mreq {
+    libc::ipv6_mreq {
+        ipv6mr_multiaddr: libc::in6_addr {
+            s6_addr: multiaddr.octets(),
+        },
+        ipv6mr_interface: interface,
+    }
+}
+
+fn secs(duration: Duration) -> libc::c_int {
+    duration.as_secs().min(libc::c_int::MAX as u64) as libc::c_int
+}
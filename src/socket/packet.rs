@@ -2,14 +2,14 @@ use std::cell::RefCell;
 use std::future::Future;
 use std::io;
 use std::net::SocketAddr;
-use std::os::unix::io::{AsRawFd, RawFd};
 use std::pin::Pin;
 use std::task::{ready, Context, Poll};
 
 use socket2::SockAddr;
 
 use super::Socket;
-use crate::driver::{self, Op};
+use crate::buffer::Buf;
+use crate::driver::{self, Fd, Op};
 
 pub(crate) struct Packet {
     inner: RefCell<Inner>,
@@ -27,6 +27,7 @@ impl Packet {
                 send_to: SendMsgState::Idle,
                 connect: ConnectState::Idle,
                 recv_multi: RecvMultiState::Idle,
+                recv_msg_multi: RecvMsgMultiState::Idle,
             }),
         }
     }
@@ -35,16 +36,18 @@ impl Packet {
         &self.io
     }
 
+    pub(crate) fn get_mut(&mut self) -> &mut Socket {
+        &mut self.io
+    }
+
     pub(crate) fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
-        self.inner
-            .borrow_mut()
-            .poll_send(cx, buf, self.io.as_raw_fd())
+        self.inner.borrow_mut().poll_send(cx, buf, self.io.target())
     }
 
     pub(crate) fn poll_connect(&self, cx: &mut Context, addr: &SockAddr) -> Poll<io::Result<()>> {
         self.inner
             .borrow_mut()
-            .poll_connect(cx, self.io.as_raw_fd(), addr)
+            .poll_connect(cx, self.io.target(), addr)
     }
 
     pub(crate) fn poll_send_to(
@@ -55,19 +58,31 @@ impl Packet {
     ) -> Poll<io::Result<usize>> {
         self.inner
             .borrow_mut()
-            .poll_send_to(cx, buf, addr, self.io.as_raw_fd())
+            .poll_send_to(cx, buf, addr, self.io.target())
     }
 
     pub(crate) fn poll_recv(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
-        self.inner
-            .borrow_mut()
-            .poll_recv(cx, buf, self.io.as_raw_fd())
+        self.inner.borrow_mut().poll_recv(cx, buf, self.io.target())
     }
 
     pub(crate) fn poll_recv2(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let buf1 = ready!(self.poll_recv_buf(cx))?;
+        let n = buf1.len();
+        buf[..n].copy_from_slice(&buf1);
+        Poll::Ready(Ok(n))
+    }
+
+    pub(crate) fn poll_recv_buf(&self, cx: &mut Context) -> Poll<io::Result<Buf>> {
+        self.inner.borrow_mut().poll_recv_buf(cx, self.io.target())
+    }
+
+    pub(crate) fn poll_recv_from_multi(
+        &self,
+        cx: &mut Context,
+    ) -> Poll<io::Result<(Buf, SocketAddr)>> {
         self.inner
             .borrow_mut()
-            .poll_recv2(cx, buf, self.io.as_raw_fd())
+            .poll_recv_from_multi(cx, self.io.target())
     }
 
     pub(crate) fn poll_recv_from(
@@ -77,7 +92,7 @@ impl Packet {
     ) -> Poll<io::Result<(usize, SocketAddr)>> {
         self.inner
             .borrow_mut()
-            .poll_recv_from(cx, buf, self.io.as_raw_fd())
+            .poll_recv_from(cx, buf, self.io.target())
     }
 }
 
@@ -88,37 +103,35 @@ struct Inner {
     send_to: SendMsgState,
     connect: ConnectState,
     recv_multi: RecvMultiState,
+    recv_msg_multi: RecvMsgMultiState,
 }
 
 impl Inner {
-    fn poll_send(&mut self, cx: &mut Context, buf: &[u8], fd: RawFd) -> Poll<io::Result<usize>> {
+    fn poll_send(&mut self, cx: &mut Context, buf: &[u8], fd: Fd) -> Poll<io::Result<usize>> {
         loop {
             match &mut self.send {
                 SendState::Idle => {
                     self.send = SendState::Sending(Op::send(fd, buf)?);
                 }
                 SendState::Sending(op) => {
-                    let n = ready!(Pin::new(op).poll(cx))?;
+                    let res = ready!(Pin::new(op).poll(cx));
                     self.send = SendState::Idle;
-                    return Poll::Ready(Ok(n));
+                    return Poll::Ready(res);
                 }
             }
         }
     }
 
-    fn poll_connect(
-        &mut self,
-        cx: &mut Context,
-        fd: RawFd,
-        addr: &SockAddr,
-    ) -> Poll<io::Result<()>> {
+    fn poll_connect(&mut self, cx: &mut Context, fd: Fd, addr: &SockAddr) -> Poll<io::Result<()>> {
         loop {
             match &mut self.connect {
                 ConnectState::Idle => {
                     self.connect = ConnectState::Connecting(Op::connect(fd, addr.clone())?);
                 }
                 ConnectState::Connecting(op) => {
-                    ready!(Pin::new(op).poll(cx))?;
+                    let res = ready!(Pin::new(op).poll(cx));
+                    self.connect = ConnectState::Idle;
+                    res?;
                     self.connect = ConnectState::Done;
                 }
                 ConnectState::Done => {
@@ -133,38 +146,34 @@ impl Inner {
         cx: &mut Context,
         buf: &[u8],
         addr: SocketAddr,
-        fd: RawFd,
+        fd: Fd,
     ) -> Poll<io::Result<usize>> {
         loop {
             match &mut self.send_to {
                 SendMsgState::Idle => {
-                    self.send_to = SendMsgState::Sending(Op::sendmsg(fd, buf, addr)?);
+                    self.send_to = SendMsgState::Sending(Op::sendmsg(fd, buf, addr, None)?);
                 }
                 SendMsgState::Sending(op) => {
-                    let n = ready!(Pin::new(op).poll(cx))?;
+                    let res = ready!(Pin::new(op).poll(cx));
                     self.send_to = SendMsgState::Idle;
-                    return Poll::Ready(Ok(n));
+                    return Poll::Ready(res);
                 }
             }
         }
     }
 
-    fn poll_recv(
-        &mut self,
-        cx: &mut Context,
-        buf: &mut [u8],
-        fd: RawFd,
-    ) -> Poll<io::Result<usize>> {
+    fn poll_recv(&mut self, cx: &mut Context, buf: &mut [u8], fd: Fd) -> Poll<io::Result<usize>> {
         loop {
             match &mut self.recv {
                 RecvState::Idle => {
                     self.recv = RecvState::Recving(Op::recv(fd, buf.len())?);
                 }
                 RecvState::Recving(op) => {
-                    let buf1 = ready!(Pin::new(op).poll(cx))?;
+                    let res = ready!(Pin::new(op).poll(cx));
+                    self.recv = RecvState::Idle;
+                    let buf1 = res?;
                     let n = buf1.len();
                     buf[..n].copy_from_slice(&buf1[..n]);
-                    self.recv = RecvState::Idle;
                     return Poll::Ready(Ok(n));
                 }
             }
@@ -175,53 +184,43 @@ impl Inner {
         &mut self,
         cx: &mut Context,
         buf: &mut [u8],
-        fd: RawFd,
+        fd: Fd,
     ) -> Poll<io::Result<(usize, SocketAddr)>> {
         loop {
             match &mut self.recv_from {
                 RecvMsgState::Idle => {
-                    self.recv_from = RecvMsgState::Recving(Op::recvmsg(fd, buf.len())?);
+                    self.recv_from = RecvMsgState::Recving(Op::recvmsg(fd, buf.len(), None)?);
                 }
                 RecvMsgState::Recving(op) => {
-                    let (buf1, addr) = ready!(Pin::new(op).poll(cx))?;
+                    let res = ready!(Pin::new(op).poll(cx));
+                    self.recv_from = RecvMsgState::Idle;
+                    let (buf1, addr, _) = res?;
                     let n = buf1.len();
                     buf[..n].copy_from_slice(&buf1[..n]);
-                    self.recv_from = RecvMsgState::Idle;
                     return Poll::Ready(Ok((n, addr)));
                 }
             }
         }
     }
 
-    fn poll_recv2(
-        &mut self,
-        cx: &mut Context,
-        buf: &mut [u8],
-        fd: RawFd,
-    ) -> Poll<io::Result<usize>> {
+    fn poll_recv_buf(&mut self, cx: &mut Context, fd: Fd) -> Poll<io::Result<Buf>> {
         loop {
             match &mut self.recv_multi {
                 RecvMultiState::Idle => {
                     self.recv_multi = RecvMultiState::Recving(Op::recv_multi(fd)?);
                 }
                 RecvMultiState::Recving(op) => {
-                    if let Some(buf1) = op.get_mut().next() {
-                        let buf1 = buf1.map_err(|err| {
-                            self.recv_multi = RecvMultiState::Done;
-                            err
+                    if let Some(buf) = op.get_mut().next() {
+                        let buf = buf.inspect_err(|err| {
+                            self.recv_multi = RecvMultiState::failed(err);
                         })?;
-                        let n = buf1.len();
-                        buf[..n].copy_from_slice(&buf1[..n]);
-                        return Poll::Ready(Ok(n));
+                        return Poll::Ready(Ok(buf));
                     }
-                    let buf1 = ready!(Pin::new(&mut *op).poll(cx)).map_err(|err| {
-                        self.recv_multi = RecvMultiState::Done;
-                        err
+                    let buf = ready!(Pin::new(&mut *op).poll(cx)).inspect_err(|err| {
+                        self.recv_multi = RecvMultiState::failed(err);
                     })?;
-                    let n = buf1.len();
-                    buf[..n].copy_from_slice(&buf1[..n]);
                     self.recv_multi = RecvMultiState::Idle;
-                    return Poll::Ready(Ok(n));
+                    return Poll::Ready(Ok(buf));
                 }
                 RecvMultiState::Done => {
                     return Poll::Ready(Err(io::ErrorKind::ConnectionAborted.into()))
@@ -229,6 +228,38 @@ impl Inner {
             }
         }
     }
+
+    fn poll_recv_from_multi(
+        &mut self,
+        cx: &mut Context,
+        fd: Fd,
+    ) -> Poll<io::Result<(Buf, SocketAddr)>> {
+        loop {
+            match &mut self.recv_msg_multi {
+                RecvMsgMultiState::Idle => {
+                    self.recv_msg_multi = RecvMsgMultiState::Recving(Op::recvmsg_multi(fd)?);
+                }
+                // A datagram that failed on its own, like a truncated one, leaves the
+                // multishot receive going.
+                RecvMsgMultiState::Recving(op) => {
+                    if let Some(res) = op.get_mut().next() {
+                        let res = res.inspect_err(|err| {
+                            self.recv_msg_multi = RecvMsgMultiState::failed(err);
+                        })?;
+                        return Poll::Ready(res);
+                    }
+                    let res = ready!(Pin::new(&mut *op).poll(cx)).inspect_err(|err| {
+                        self.recv_msg_multi = RecvMsgMultiState::failed(err);
+                    })?;
+                    self.recv_msg_multi = RecvMsgMultiState::Idle;
+                    return Poll::Ready(res);
+                }
+                RecvMsgMultiState::Done => {
+                    return Poll::Ready(Err(io::ErrorKind::ConnectionAborted.into()))
+                }
+            }
+        }
+    }
 }
 
 enum ConnectState {
@@ -257,8 +288,41 @@ enum RecvMsgState {
     Recving(Op<driver::RecvMsg>),
 }
 
+// Whether a multishot receive failing with `err` can be armed again. `ENOBUFS` only means
+// that the buffer ring ran dry, other failures are for good.
+pub(crate) fn is_transient(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENOBUFS)
+}
+
 enum RecvMultiState {
     Idle,
     Recving(Op<driver::RecvMulti>),
     Done,
 }
+
+impl RecvMultiState {
+    // The state after the receive failed with `err`, `Idle` to arm it again on the next call.
+    fn failed(err: &io::Error) -> RecvMultiState {
+        if is_transient(err) {
+            RecvMultiState::Idle
+        } else {
+            RecvMultiState::Done
+        }
+    }
+}
+
+enum RecvMsgMultiState {
+    Idle,
+    Recving(Op<driver::RecvMsgMulti>),
+    Done,
+}
+
+impl RecvMsgMultiState {
+    fn failed(err: &io::Error) -> RecvMsgMultiState {
+        if is_transient(err) {
+            RecvMsgMultiState::Idle
+        } else {
+            RecvMsgMultiState::Done
+        }
+    }
+}
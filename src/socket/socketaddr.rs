@@ -4,6 +4,7 @@ use std::fmt;
 use std::io;
 use std::mem;
 use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::AsRawFd;
 use std::path::Path;
 
 use super::SocketStorage;
@@ -120,3 +121,15 @@ impl From<SocketStorage> for SocketAddr {
         SocketAddr::from_parts(sockaddr, socklen)
     }
 }
+
+// The local address of the unix socket `socket`.
+pub(crate) fn local(socket: &impl AsRawFd) -> io::Result<SocketAddr> {
+    let fd = socket.as_raw_fd();
+    SocketAddr::new(|sockaddr, socklen| syscall!(getsockname(fd, sockaddr, socklen)))
+}
+
+// The address of the peer of the unix socket `socket`.
+pub(crate) fn peer(socket: &impl AsRawFd) -> io::Result<SocketAddr> {
+    let fd = socket.as_raw_fd();
+    SocketAddr::new(|sockaddr, socklen| syscall!(getpeername(fd, sockaddr, socklen)))
+}
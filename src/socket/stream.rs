@@ -1,15 +1,16 @@
 use std::future::Future;
-use std::io;
+use std::io::{self, IoSlice, IoSliceMut};
+use std::mem;
 use std::net;
-use std::os::unix::io::{AsRawFd, RawFd};
 use std::pin::Pin;
+use std::rc::Rc;
 use std::task::{ready, Context, Poll};
 
 use socket2::SockAddr;
 
 use super::Socket;
 use crate::buffer::Buf;
-use crate::driver::{self, Op};
+use crate::driver::{self, Fd, Op};
 
 const DEFAULT_BUFFER_SIZE: u32 = 4096;
 
@@ -28,8 +29,14 @@ impl Stream {
                     buf: None,
                     state: ReadState::Idle,
                 },
-                write: WriteState::Idle,
-                shutdown: ShutdownState::Idle,
+                write: Write {
+                    state: WriteState::Idle,
+                    shutdown: ShutdownState::Idle,
+                    buf: Vec::new(),
+                    spare: Vec::new(),
+                    flushing: 0,
+                    capacity: 0,
+                },
                 connect: ConnectState::Idle,
             },
         }
@@ -39,12 +46,16 @@ impl Stream {
         &self.io
     }
 
+    pub(crate) fn get_mut(&mut self) -> &mut Socket {
+        &mut self.io
+    }
+
     pub(crate) fn poll_connect(
         &mut self,
         cx: &mut Context,
         addr: &SockAddr,
     ) -> Poll<io::Result<()>> {
-        self.inner.poll_connect(cx, self.io.as_raw_fd(), addr)
+        self.inner.poll_connect(cx, self.io.target(), addr)
     }
 
     pub(crate) fn poll_read(
@@ -52,23 +63,51 @@ impl Stream {
         cx: &mut Context,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        let src = ready!(self.inner.poll_fill_buf(cx, self.io.as_raw_fd()))?;
-        let n = buf.len().min(src.len());
-        buf[..n].copy_from_slice(&src[..n]);
-        self.inner.consume(n);
-        Poll::Ready(Ok(n))
+        self.inner.read.poll_read(cx, self.io.target(), buf)
+    }
+
+    pub(crate) fn poll_read_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.inner
+            .read
+            .poll_read_vectored(cx, self.io.target(), bufs)
     }
 
     pub(crate) fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
-        self.inner.poll_fill_buf(cx, self.io.as_raw_fd())
+        self.inner.read.poll_fill_buf(cx, self.io.target())
     }
 
     pub(crate) fn consume(&mut self, amt: usize) {
-        self.inner.consume(amt)
+        self.inner.read.consume(amt)
+    }
+
+    pub(crate) fn take_buffered(&mut self) -> Vec<u8> {
+        self.inner.read.take_buffered()
     }
 
     pub(crate) fn poll_write(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
-        self.inner.poll_write(cx, buf, self.io.as_raw_fd())
+        self.inner.write.poll_write(cx, buf, self.io.target())
+    }
+
+    pub(crate) fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.inner
+            .write
+            .poll_write_vectored(cx, bufs, self.io.target())
+    }
+
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        self.inner.write.poll_flush(cx, self.io.target())
+    }
+
+    pub(crate) fn set_write_buffer(&mut self, capacity: usize) {
+        self.inner.write.set_capacity(capacity)
     }
 
     pub(crate) fn poll_shutdown(
@@ -76,19 +115,63 @@ impl Stream {
         cx: &mut Context,
         how: net::Shutdown,
     ) -> Poll<io::Result<()>> {
-        let how = match how {
-            net::Shutdown::Write => libc::SHUT_WR,
-            net::Shutdown::Read => libc::SHUT_RD,
-            net::Shutdown::Both => libc::SHUT_RDWR,
+        self.inner.write.poll_shutdown(cx, self.io.target(), how)
+    }
+
+    pub(crate) fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        let read = ReadHalf {
+            io: &self.io,
+            read: &mut self.inner.read,
         };
-        self.inner.poll_shutdown(cx, self.io.as_raw_fd(), how)
+        let write = WriteHalf {
+            io: &self.io,
+            write: &mut self.inner.write,
+        };
+        (read, write)
+    }
+
+    pub(crate) fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        let io = Rc::new(self.io);
+        let read = OwnedReadHalf {
+            io: io.clone(),
+            read: Box::new(self.inner.read),
+        };
+        let write = OwnedWriteHalf {
+            io,
+            write: Box::new(self.inner.write),
+        };
+        (read, write)
+    }
+
+    // Put back together the halves returned by `into_split`, or hand them back if they come
+    // from different streams.
+    pub(crate) fn reunite(
+        read: OwnedReadHalf,
+        write: OwnedWriteHalf,
+    ) -> Result<Stream, (OwnedReadHalf, OwnedWriteHalf)> {
+        if !Rc::ptr_eq(&read.io, &write.io) {
+            return Err((read, write));
+        }
+        let OwnedWriteHalf { io, write } = write;
+        drop(io);
+        let OwnedReadHalf { io, read } = read;
+        let io = Rc::try_unwrap(io)
+            .ok()
+            .expect("stream halves are the only owners of the socket");
+        Ok(Stream {
+            io,
+            inner: Inner {
+                read: *read,
+                write: *write,
+                connect: ConnectState::Done,
+            },
+        })
     }
 }
 
 struct Inner {
     read: Read,
-    write: WriteState,
-    shutdown: ShutdownState,
+    write: Write,
     connect: ConnectState,
 }
 
@@ -101,6 +184,28 @@ enum ConnectState {
 enum WriteState {
     Idle,
     Writing(Op<driver::Write>),
+    // A write of the caller's bytes completed during a flush, the result is returned by the
+    // next write, or by the flush if it is an error.
+    Written(io::Result<usize>),
+    // Writing the start of the write buffer, which the operation owns meanwhile.
+    Flushing(Op<driver::Write>),
+}
+
+impl WriteState {
+    // Wait for the write of the caller's bytes in flight, which may have been started by a
+    // call with another buffer, and return to `Idle`.
+    fn poll_complete(&mut self, cx: &mut Context) -> Poll<io::Result<usize>> {
+        let res = match self {
+            WriteState::Writing(op) => ready!(Pin::new(op).poll(cx)).0,
+            WriteState::Written(_) => match mem::replace(self, WriteState::Idle) {
+                WriteState::Written(res) => res,
+                _ => unreachable!(),
+            },
+            WriteState::Idle | WriteState::Flushing(_) => unreachable!("no write in flight"),
+        };
+        *self = WriteState::Idle;
+        Poll::Ready(res)
+    }
 }
 
 enum ReadState {
@@ -115,11 +220,42 @@ struct Read {
 }
 
 impl Read {
-    fn poll_fill_buf(&mut self, cx: &mut Context, fd: RawFd) -> Poll<io::Result<&[u8]>> {
+    fn poll_read(&mut self, cx: &mut Context, fd: Fd, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let src = ready!(self.poll_fill_buf(cx, fd))?;
+        let n = buf.len().min(src.len());
+        buf[..n].copy_from_slice(&src[..n]);
+        self.consume(n);
+        Poll::Ready(Ok(n))
+    }
+
+    // Fill the buffers in order by copying from a single buffered read, like `poll_read`,
+    // rather than doing a `readv` into them: the bytes land in a provided buffer first.
+    fn poll_read_vectored(
+        &mut self,
+        cx: &mut Context,
+        fd: Fd,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let mut src = ready!(self.poll_fill_buf(cx, fd))?;
+        let mut n = 0;
+        for buf in bufs.iter_mut() {
+            if src.is_empty() {
+                break;
+            }
+            let len = buf.len().min(src.len());
+            buf[..len].copy_from_slice(&src[..len]);
+            src = &src[len..];
+            n += len;
+        }
+        self.consume(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_fill_buf(&mut self, cx: &mut Context, fd: Fd) -> Poll<io::Result<&[u8]>> {
         loop {
             match &mut self.state {
                 ReadState::Idle => {
-                    if self.buf.is_some() && !self.buf.as_ref().unwrap()[self.pos..].is_empty() {
+                    if self.buf.as_ref().is_some_and(|buf| buf.len() > self.pos) {
                         return Poll::Ready(Ok(&self.buf.as_ref().unwrap()[self.pos..]));
                     }
                     self.pos = 0;
@@ -143,6 +279,18 @@ impl Read {
     fn consume(&mut self, amt: usize) {
         self.pos += amt;
     }
+
+    // Take the bytes read from the socket but not consumed yet, so that I/O bypassing the
+    // buffer does not reorder them.
+    fn take_buffered(&mut self) -> Vec<u8> {
+        let buffered = match &self.buf {
+            Some(buf) if buf.len() > self.pos => buf[self.pos..].to_vec(),
+            _ => Vec::new(),
+        };
+        self.buf = None;
+        self.pos = 0;
+        buffered
+    }
 }
 
 enum ShutdownState {
@@ -152,12 +300,7 @@ enum ShutdownState {
 }
 
 impl Inner {
-    fn poll_connect(
-        &mut self,
-        cx: &mut Context,
-        fd: RawFd,
-        addr: &SockAddr,
-    ) -> Poll<io::Result<()>> {
+    fn poll_connect(&mut self, cx: &mut Context, fd: Fd, addr: &SockAddr) -> Poll<io::Result<()>> {
         loop {
             match &mut self.connect {
                 ConnectState::Idle => {
@@ -173,16 +316,43 @@ impl Inner {
             }
         }
     }
+}
+
+// Unless `capacity` is 0, writes smaller than it are copied to `buf` and acknowledged at
+// once, the buffer is written out as one operation when it fills up or on flush. Larger
+// writes go straight to the socket once the buffer is empty.
+struct Write {
+    state: WriteState,
+    shutdown: ShutdownState,
+    buf: Vec<u8>,
+    // The buffer taking the writes while `buf` is being flushed, empty.
+    spare: Vec<u8>,
+    // The length of the buffer being flushed.
+    flushing: usize,
+    capacity: usize,
+}
+
+impl Write {
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.buf.reserve(capacity.saturating_sub(self.buf.len()));
+    }
 
     fn poll_shutdown(
         &mut self,
         cx: &mut Context,
-        fd: RawFd,
-        how: libc::c_int,
+        fd: Fd,
+        how: net::Shutdown,
     ) -> Poll<io::Result<()>> {
+        let how = match how {
+            net::Shutdown::Write => libc::SHUT_WR,
+            net::Shutdown::Read => libc::SHUT_RD,
+            net::Shutdown::Both => libc::SHUT_RDWR,
+        };
         loop {
             match &mut self.shutdown {
                 ShutdownState::Idle => {
+                    ready!(self.poll_flush(cx, fd))?;
                     self.shutdown = ShutdownState::Shutdowning(Op::shutdown(fd, how)?);
                 }
                 ShutdownState::Shutdowning(op) => {
@@ -196,26 +366,494 @@ impl Inner {
         }
     }
 
-    fn poll_write(&mut self, cx: &mut Context, buf: &[u8], fd: RawFd) -> Poll<io::Result<usize>> {
+    fn poll_write(&mut self, cx: &mut Context, buf: &[u8], fd: Fd) -> Poll<io::Result<usize>> {
+        self.poll_write_with(cx, fd, buf.len(), |dst| dst.extend_from_slice(buf))
+    }
+
+    // The slices are gathered into a single write: the operation owns a copy of the bytes,
+    // since the caller's buffers may be gone before it completes.
+    fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &[IoSlice<'_>],
+        fd: Fd,
+    ) -> Poll<io::Result<usize>> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        if len == 0 {
+            return Poll::Ready(Ok(0));
+        }
+        self.poll_write_with(cx, fd, len, |dst| {
+            bufs.iter().for_each(|buf| dst.extend_from_slice(buf))
+        })
+    }
+
+    // Write `len` bytes copied by `extend`, either to the write buffer, or to a buffer of their
+    // own written to the socket directly.
+    fn poll_write_with(
+        &mut self,
+        cx: &mut Context,
+        fd: Fd,
+        len: usize,
+        extend: impl Fn(&mut Vec<u8>),
+    ) -> Poll<io::Result<usize>> {
         loop {
-            match &mut self.write {
+            let fits = self.flushing + self.buf.len() + len <= self.capacity;
+            match &mut self.state {
                 WriteState::Idle => {
-                    self.write = WriteState::Writing(Op::write(fd, buf)?);
+                    if self.buf.is_empty() && len >= self.capacity {
+                        let mut buf = Vec::with_capacity(len);
+                        extend(&mut buf);
+                        self.state = WriteState::Writing(Op::write(fd, buf)?);
+                    } else if fits {
+                        extend(&mut self.buf);
+                        if self.buf.len() >= self.capacity {
+                            self.start_flush(fd)?;
+                        }
+                        return Poll::Ready(Ok(len));
+                    } else {
+                        self.start_flush(fd)?;
+                    }
                 }
-                WriteState::Writing(op) => {
-                    let n = ready!(Pin::new(&mut *op).poll(cx))?;
-                    self.write = WriteState::Idle;
-                    return Poll::Ready(Ok(n));
+                // The operation owns the buffer being written, later bytes are buffered in
+                // the spare one meanwhile.
+                WriteState::Flushing(_) if fits => {
+                    extend(&mut self.buf);
+                    return Poll::Ready(Ok(len));
                 }
+                WriteState::Flushing(_) => ready!(self.poll_flushing(cx))?,
+                state => return state.poll_complete(cx),
             }
         }
     }
 
-    fn poll_fill_buf(&mut self, cx: &mut Context, fd: RawFd) -> Poll<io::Result<&[u8]>> {
-        self.read.poll_fill_buf(cx, fd)
+    // Write out the write buffer, and wait for the write in flight to complete.
+    fn poll_flush(&mut self, cx: &mut Context, fd: Fd) -> Poll<io::Result<()>> {
+        loop {
+            match &mut self.state {
+                WriteState::Idle if self.buf.is_empty() => return Poll::Ready(Ok(())),
+                WriteState::Idle => self.start_flush(fd)?,
+                WriteState::Flushing(_) => ready!(self.poll_flushing(cx))?,
+                WriteState::Writing(_) => {
+                    let res = ready!(self.state.poll_complete(cx));
+                    self.state = WriteState::Written(res);
+                }
+                // A failed write is reported by the flush, a successful one is kept for the
+                // next write to return its length.
+                WriteState::Written(Err(_)) => return self.state.poll_complete(cx).map_ok(drop),
+                // Bytes are only written directly while the buffer is empty.
+                WriteState::Written(Ok(_)) => return Poll::Ready(Ok(())),
+            }
+        }
     }
 
-    fn consume(&mut self, amt: usize) {
-        self.read.consume(amt);
+    // Hand the write buffer over to a write, without copying it.
+    fn start_flush(&mut self, fd: Fd) -> io::Result<()> {
+        let buf = mem::replace(&mut self.buf, mem::take(&mut self.spare));
+        self.flushing = buf.len();
+        self.state = WriteState::Flushing(Op::write(fd, buf)?);
+        Ok(())
+    }
+
+    fn poll_flushing(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        if let WriteState::Flushing(op) = &mut self.state {
+            let (res, mut buf) = ready!(Pin::new(op).poll(cx));
+            self.state = WriteState::Idle;
+            self.flushing = 0;
+            // The bytes left unwritten go back in front of those buffered meanwhile.
+            buf.drain(..*res.as_ref().unwrap_or(&0));
+            buf.extend_from_slice(&self.buf);
+            self.buf.clear();
+            self.spare = mem::replace(&mut self.buf, buf);
+            if res? == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+        }
+        Poll::Ready(Ok(()))
     }
 }
+
+pub(crate) struct ReadHalf<'a> {
+    io: &'a Socket,
+    read: &'a mut Read,
+}
+
+impl ReadHalf<'_> {
+    pub(crate) fn get_ref(&self) -> &Socket {
+        self.io
+    }
+
+    pub(crate) fn poll_read(
+        &mut self,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.read.poll_read(cx, self.io.target(), buf)
+    }
+
+    pub(crate) fn poll_read_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.read.poll_read_vectored(cx, self.io.target(), bufs)
+    }
+
+    pub(crate) fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.read.poll_fill_buf(cx, self.io.target())
+    }
+
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.read.consume(amt)
+    }
+}
+
+pub(crate) struct WriteHalf<'a> {
+    io: &'a Socket,
+    write: &'a mut Write,
+}
+
+impl WriteHalf<'_> {
+    pub(crate) fn get_ref(&self) -> &Socket {
+        self.io
+    }
+
+    pub(crate) fn poll_write(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.write.poll_write(cx, buf, self.io.target())
+    }
+
+    pub(crate) fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.write.poll_write_vectored(cx, bufs, self.io.target())
+    }
+
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        self.write.poll_flush(cx, self.io.target())
+    }
+
+    pub(crate) fn set_write_buffer(&mut self, capacity: usize) {
+        self.write.set_capacity(capacity)
+    }
+
+    pub(crate) fn poll_shutdown(
+        &mut self,
+        cx: &mut Context,
+        how: net::Shutdown,
+    ) -> Poll<io::Result<()>> {
+        self.write.poll_shutdown(cx, self.io.target(), how)
+    }
+}
+
+// The states are boxed to keep the halves, and the error returned by `reunite`, small.
+pub(crate) struct OwnedReadHalf {
+    io: Rc<Socket>,
+    read: Box<Read>,
+}
+
+impl OwnedReadHalf {
+    pub(crate) fn get_ref(&self) -> &Socket {
+        &self.io
+    }
+
+    pub(crate) fn poll_read(
+        &mut self,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.read.poll_read(cx, self.io.target(), buf)
+    }
+
+    pub(crate) fn poll_read_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.read.poll_read_vectored(cx, self.io.target(), bufs)
+    }
+
+    pub(crate) fn poll_fill_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.read.poll_fill_buf(cx, self.io.target())
+    }
+
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.read.consume(amt)
+    }
+}
+
+pub(crate) struct OwnedWriteHalf {
+    io: Rc<Socket>,
+    write: Box<Write>,
+}
+
+impl OwnedWriteHalf {
+    pub(crate) fn get_ref(&self) -> &Socket {
+        &self.io
+    }
+
+    pub(crate) fn poll_write(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.write.poll_write(cx, buf, self.io.target())
+    }
+
+    pub(crate) fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.write.poll_write_vectored(cx, bufs, self.io.target())
+    }
+
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        self.write.poll_flush(cx, self.io.target())
+    }
+
+    pub(crate) fn set_write_buffer(&mut self, capacity: usize) {
+        self.write.set_capacity(capacity)
+    }
+
+    pub(crate) fn poll_shutdown(
+        &mut self,
+        cx: &mut Context,
+        how: net::Shutdown,
+    ) -> Poll<io::Result<()>> {
+        self.write.poll_shutdown(cx, self.io.target(), how)
+    }
+}
+
+// Define the public read and write halves of the stream type `$stream`, wrapping those of
+// `Stream`, along with the `split` and `into_split` functions creating them. The addresses of
+// the halves are looked up with `$local_addr` and `$peer_addr`, given the socket.
+macro_rules! stream_halves {
+    ($stream:ident, $addr:ty, $local_addr:path, $peer_addr:path) => {
+        #[doc = concat!("The read half of a [`", stringify!($stream), "`], borrowed with [`", stringify!($stream), "::split`].")]
+        pub struct ReadHalf<'a> {
+            inner: $crate::socket::ReadHalf<'a>,
+        }
+
+        #[doc = concat!("The write half of a [`", stringify!($stream), "`], borrowed with [`", stringify!($stream), "::split`].")]
+        ///
+        /// Closing it shuts down the write side of the stream.
+        pub struct WriteHalf<'a> {
+            inner: $crate::socket::WriteHalf<'a>,
+        }
+
+        #[doc = concat!("The owned read half of a [`", stringify!($stream), "`], created with [`", stringify!($stream), "::into_split`].")]
+        pub struct OwnedReadHalf {
+            inner: $crate::socket::OwnedReadHalf,
+        }
+
+        #[doc = concat!("The owned write half of a [`", stringify!($stream), "`], created with [`", stringify!($stream), "::into_split`].")]
+        ///
+        /// Closing it shuts down the write side of the stream.
+        pub struct OwnedWriteHalf {
+            inner: $crate::socket::OwnedWriteHalf,
+        }
+
+        /// Error returned by `reunite` when the two halves come from different streams. The halves
+        /// are handed back unchanged.
+        pub struct ReuniteError(pub OwnedReadHalf, pub OwnedWriteHalf);
+
+        pub(super) fn split(stream: &mut $crate::socket::Stream) -> (ReadHalf<'_>, WriteHalf<'_>) {
+            let (read, write) = stream.split();
+            (ReadHalf { inner: read }, WriteHalf { inner: write })
+        }
+
+        pub(super) fn into_split(stream: $crate::socket::Stream) -> (OwnedReadHalf, OwnedWriteHalf) {
+            let (read, write) = stream.into_split();
+            (
+                OwnedReadHalf { inner: read },
+                OwnedWriteHalf { inner: write },
+            )
+        }
+
+        fn reunite(read: OwnedReadHalf, write: OwnedWriteHalf) -> Result<$stream, ReuniteError> {
+            $crate::socket::Stream::reunite(read.inner, write.inner)
+                .map($stream::from_inner)
+                .map_err(|(read, write)| {
+                    ReuniteError(
+                        OwnedReadHalf { inner: read },
+                        OwnedWriteHalf { inner: write },
+                    )
+                })
+        }
+
+        impl ReadHalf<'_> {
+            pub fn local_addr(&self) -> std::io::Result<$addr> {
+                $local_addr(self.inner.get_ref())
+            }
+
+            pub fn peer_addr(&self) -> std::io::Result<$addr> {
+                $peer_addr(self.inner.get_ref())
+            }
+        }
+
+        impl futures_io::AsyncRead for ReadHalf<'_> {
+            fn poll_read(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &mut [u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_read(cx, buf)
+            }
+
+            fn poll_read_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &mut [std::io::IoSliceMut<'_>],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_read_vectored(cx, bufs)
+            }
+        }
+
+        impl futures_io::AsyncBufRead for ReadHalf<'_> {
+            fn poll_fill_buf(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<&[u8]>> {
+                self.get_mut().inner.poll_fill_buf(cx)
+            }
+
+            fn consume(self: std::pin::Pin<&mut Self>, amt: usize) {
+                self.get_mut().inner.consume(amt);
+            }
+        }
+
+        impl WriteHalf<'_> {
+            #[doc = concat!("Buffer small writes, see [`", stringify!($stream), "::set_write_buffer`]. The buffer is shared with")]
+            /// the stream the half comes from.
+            pub fn set_write_buffer(&mut self, capacity: usize) {
+                self.inner.set_write_buffer(capacity)
+            }
+
+            pub fn local_addr(&self) -> std::io::Result<$addr> {
+                $local_addr(self.inner.get_ref())
+            }
+
+            pub fn peer_addr(&self) -> std::io::Result<$addr> {
+                $peer_addr(self.inner.get_ref())
+            }
+        }
+
+        impl futures_io::AsyncWrite for WriteHalf<'_> {
+            fn poll_write(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context, buf: &[u8]) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_write(cx, buf)
+            }
+
+            fn poll_write_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &[std::io::IoSlice<'_>],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_write_vectored(cx, bufs)
+            }
+
+            fn poll_flush(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context) -> std::task::Poll<std::io::Result<()>> {
+                self.get_mut().inner.poll_flush(cx)
+            }
+
+            fn poll_close(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context) -> std::task::Poll<std::io::Result<()>> {
+                self.get_mut().inner.poll_shutdown(cx, std::net::Shutdown::Write)
+            }
+        }
+
+        impl OwnedReadHalf {
+            /// Put the halves back together into the stream they were split from.
+            pub fn reunite(self, other: OwnedWriteHalf) -> Result<$stream, ReuniteError> {
+                reunite(self, other)
+            }
+
+            pub fn local_addr(&self) -> std::io::Result<$addr> {
+                $local_addr(self.inner.get_ref())
+            }
+
+            pub fn peer_addr(&self) -> std::io::Result<$addr> {
+                $peer_addr(self.inner.get_ref())
+            }
+        }
+
+        impl futures_io::AsyncRead for OwnedReadHalf {
+            fn poll_read(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                buf: &mut [u8],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_read(cx, buf)
+            }
+
+            fn poll_read_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &mut [std::io::IoSliceMut<'_>],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_read_vectored(cx, bufs)
+            }
+        }
+
+        impl futures_io::AsyncBufRead for OwnedReadHalf {
+            fn poll_fill_buf(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<&[u8]>> {
+                self.get_mut().inner.poll_fill_buf(cx)
+            }
+
+            fn consume(self: std::pin::Pin<&mut Self>, amt: usize) {
+                self.get_mut().inner.consume(amt);
+            }
+        }
+
+        impl OwnedWriteHalf {
+            /// Put the halves back together into the stream they were split from.
+            pub fn reunite(self, other: OwnedReadHalf) -> Result<$stream, ReuniteError> {
+                reunite(other, self)
+            }
+
+            #[doc = concat!("Buffer small writes, see [`", stringify!($stream), "::set_write_buffer`]. The buffer is shared with")]
+            /// the stream the half comes from.
+            pub fn set_write_buffer(&mut self, capacity: usize) {
+                self.inner.set_write_buffer(capacity)
+            }
+
+            pub fn local_addr(&self) -> std::io::Result<$addr> {
+                $local_addr(self.inner.get_ref())
+            }
+
+            pub fn peer_addr(&self) -> std::io::Result<$addr> {
+                $peer_addr(self.inner.get_ref())
+            }
+        }
+
+        impl futures_io::AsyncWrite for OwnedWriteHalf {
+            fn poll_write(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context, buf: &[u8]) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_write(cx, buf)
+            }
+
+            fn poll_write_vectored(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+                bufs: &[std::io::IoSlice<'_>],
+            ) -> std::task::Poll<std::io::Result<usize>> {
+                self.get_mut().inner.poll_write_vectored(cx, bufs)
+            }
+
+            fn poll_flush(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context) -> std::task::Poll<std::io::Result<()>> {
+                self.get_mut().inner.poll_flush(cx)
+            }
+
+            fn poll_close(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context) -> std::task::Poll<std::io::Result<()>> {
+                self.get_mut().inner.poll_shutdown(cx, std::net::Shutdown::Write)
+            }
+        }
+
+        impl std::fmt::Debug for ReuniteError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("ReuniteError(..)")
+            }
+        }
+
+        impl std::fmt::Display for ReuniteError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("tried to reunite halves that are not from the same socket")
+            }
+        }
+
+        impl std::error::Error for ReuniteError {}
+    };
+}
+
+pub(crate) use stream_halves;
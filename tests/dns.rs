@@ -0,0 +1,58 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use futures_util::future::join;
+use slings::net::dns::Resolver;
+use slings::net::UdpSocket;
+
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+
+// Answer `queries` queries on `socket` like a name server, with `v4` and `v6` as the address
+// records.
+async fn serve(socket: &UdpSocket, queries: usize, v4: Ipv4Addr, v6: Ipv6Addr) -> io::Result<()> {
+    let mut buf = [0; 512];
+    for _ in 0..queries {
+        let (n, peer) = socket.recv_from(&mut buf).await?;
+        let mut response = buf[..n].to_vec();
+        let qtype = u16::from_be_bytes([buf[n - 4], buf[n - 3]]);
+        let rdata = match qtype {
+            TYPE_A => v4.octets().to_vec(),
+            TYPE_AAAA => v6.octets().to_vec(),
+            _ => panic!("unexpected query type {qtype}"),
+        };
+        // Set the response flag and one answer record, named with a pointer to the question.
+        response[2] |= 0x80;
+        response[6..8].copy_from_slice(&1u16.to_be_bytes());
+        response.extend_from_slice(&[0xc0, 12]);
+        response.extend_from_slice(&qtype.to_be_bytes());
+        response.extend_from_slice(&[0, 1, 0, 0, 0, 60]);
+        response.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        response.extend_from_slice(&rdata);
+        socket.send_to(&response, peer).await?;
+    }
+    Ok(())
+}
+
+#[test]
+fn lookup_against_local_name_server() -> io::Result<()> {
+    slings::block_on(async {
+        let server = UdpSocket::bind("127.0.0.1:0").await?;
+        let resolver = Resolver::with_nameservers(vec![server.local_addr()?]);
+        let v4 = Ipv4Addr::new(192, 0, 2, 1);
+        let v6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+        let (served, ips) = join(
+            serve(&server, 2, v4, v6),
+            resolver.lookup_ip("host.example."),
+        )
+        .await;
+        served?;
+        assert_eq!(ips?, [IpAddr::V6(v6), IpAddr::V4(v4)]);
+
+        // The answers are cached, the name server is not asked again.
+        let addrs: Vec<_> = resolver.lookup_host(("host.example", 80)).await?.collect();
+        assert_eq!(addrs, [(v6, 80).into(), (v4, 80).into()]);
+        Ok(())
+    })
+}
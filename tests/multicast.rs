@@ -0,0 +1,33 @@
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use slings::net::UdpSocket;
+use slings::time::timeout;
+
+#[test]
+fn multicast_v4_on_loopback() -> io::Result<()> {
+    slings::block_on(async {
+        let group = Ipv4Addr::new(239, 255, 42, 99);
+        let receiver = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        receiver.join_multicast_v4(&group, &Ipv4Addr::LOCALHOST)?;
+        let port = receiver.local_addr()?.port();
+
+        let sender = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let lo = unsafe { libc::if_nametoindex(c"lo".as_ptr()) };
+        sender.set_multicast_if(lo)?;
+        sender.set_multicast_loop_v4(true)?;
+        sender
+            .send_to(b"hello", SocketAddr::from((group, port)))
+            .await?;
+
+        let mut buf = [0; 16];
+        let (n, from) = timeout(Duration::from_secs(5), receiver.recv_from(&mut buf))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+        assert_eq!(&buf[..n], b"hello");
+        assert_eq!(from, sender.local_addr()?);
+
+        receiver.leave_multicast_v4(&group, &Ipv4Addr::LOCALHOST)
+    })
+}
@@ -0,0 +1,45 @@
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use futures_util::io::{AsyncReadExt, AsyncWriteExt};
+use slings::net::{TcpListener, TcpStream};
+
+#[test]
+fn accept_direct_read_write() -> io::Result<()> {
+    slings::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let mut client = TcpStream::connect(listener.local_addr()?).await?;
+        let (mut stream, peer) = listener.accept_direct().await?;
+        assert_eq!(peer, client.local_addr()?);
+        assert_eq!(stream.as_raw_fd(), -1);
+
+        client.write_all(b"ping").await?;
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"ping");
+
+        stream.write_all(b"pong").await?;
+        client.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"pong");
+
+        // System calls need a file descriptor, which the stream lacks.
+        let err = stream.set_nodelay(true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        let err = stream.peer_addr().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        Ok(())
+    })
+}
+
+#[test]
+fn bind_does_not_share_the_port() -> io::Result<()> {
+    slings::block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let err = TcpListener::bind(listener.local_addr()?)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        Ok(())
+    })
+}